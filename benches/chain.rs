@@ -0,0 +1,147 @@
+use chaining_iter::IterChain;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const SEGMENTS: usize = 16;
+const SEGMENT_LEN: usize = 4096;
+
+fn ranges() -> impl DoubleEndedIterator<Item = std::ops::Range<usize>> {
+    (0..SEGMENTS).map(|i| i * SEGMENT_LEN..(i + 1) * SEGMENT_LEN)
+}
+
+fn next_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("next");
+
+    group.bench_function("IterChain", |b| {
+        b.iter(|| {
+            let mut chain = IterChain::new();
+            for r in ranges() {
+                chain.include(r);
+            }
+
+            let mut sum = 0;
+            for val in chain {
+                sum += black_box(val);
+            }
+            sum
+        })
+    });
+
+    group.bench_function("Chain", |b| {
+        b.iter(|| {
+            // `Chain` has to be nested at compile time, so only two segments are chained here
+            // with the same total length.
+            let half = SEGMENTS * SEGMENT_LEN / 2;
+            let chain = (0..half).chain(half..half * 2);
+
+            let mut sum = 0;
+            for val in chain {
+                sum += black_box(val);
+            }
+            sum
+        })
+    });
+
+    group.bench_function("Flatten", |b| {
+        b.iter(|| {
+            let chain = ranges().flatten();
+
+            let mut sum = 0;
+            for val in chain {
+                sum += black_box(val);
+            }
+            sum
+        })
+    });
+
+    group.finish();
+}
+
+fn fold(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fold");
+
+    group.bench_function("IterChain", |b| {
+        b.iter(|| {
+            let mut chain = IterChain::new();
+            for r in ranges() {
+                chain.include(r);
+            }
+            chain.map(black_box).sum::<usize>()
+        })
+    });
+
+    group.bench_function("Flatten", |b| {
+        b.iter(|| ranges().flatten().map(black_box).sum::<usize>())
+    });
+
+    group.finish();
+}
+
+fn double_ended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("double_ended");
+
+    group.bench_function("IterChain", |b| {
+        b.iter(|| {
+            let mut chain = IterChain::new();
+            for r in ranges() {
+                chain.include(r);
+            }
+
+            let mut sum = 0;
+            while let (Some(a), Some(b)) = (chain.next(), chain.next_back()) {
+                sum += black_box(a) + black_box(b);
+            }
+            sum
+        })
+    });
+
+    group.bench_function("Flatten", |b| {
+        b.iter(|| {
+            let mut chain = ranges().flatten();
+
+            let mut sum = 0;
+            while let (Some(a), Some(b)) = (chain.next(), chain.next_back()) {
+                sum += black_box(a) + black_box(b);
+            }
+            sum
+        })
+    });
+
+    group.finish();
+}
+
+fn many_short(c: &mut Criterion) {
+    let mut group = c.benchmark_group("many_short");
+    let segments = SEGMENTS * SEGMENT_LEN / 4;
+
+    group.bench_function("IterChain", |b| {
+        b.iter(|| {
+            let mut chain = IterChain::new();
+            for i in 0..segments {
+                chain.include(i * 4..i * 4 + 4);
+            }
+
+            let mut sum = 0;
+            for val in chain {
+                sum += black_box(val);
+            }
+            sum
+        })
+    });
+
+    group.bench_function("Flatten", |b| {
+        b.iter(|| {
+            let chain = (0..segments).flat_map(|i| i * 4..i * 4 + 4);
+
+            let mut sum = 0;
+            for val in chain {
+                sum += black_box(val);
+            }
+            sum
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, next_loop, fold, double_ended, many_short);
+criterion_main!(benches);
@@ -0,0 +1,384 @@
+//! A chain of byte buffers, read without copying them together.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, IoSlice, Read, Write};
+
+#[cfg(feature = "bytes")]
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// A byte buffer that can be included in a `ByteChain`.
+pub trait ByteSegment: AsRef<[u8]> {
+    /// Copy the given range out of the buffer. Buffers that are already `Bytes` share their
+    /// memory instead.
+    #[cfg(feature = "bytes")]
+    fn slice_to_bytes(&self, range: std::ops::Range<usize>) -> Bytes {
+        Bytes::copy_from_slice(&self.as_ref()[range])
+    }
+}
+
+impl ByteSegment for Vec<u8> {}
+
+impl ByteSegment for Box<[u8]> {}
+
+impl ByteSegment for &[u8] {}
+
+#[cfg(feature = "bytes")]
+impl ByteSegment for Bytes {
+    fn slice_to_bytes(&self, range: std::ops::Range<usize>) -> Bytes {
+        self.slice(range)
+    }
+}
+
+/// A buffer in a `ByteChain`, along with how much of it has been read.
+#[derive(Debug, Clone)]
+struct Chunk<B> {
+    buf: B,
+    pos: usize,
+}
+
+impl<B> Chunk<B>
+where
+    B: ByteSegment,
+{
+    fn rest(&self) -> &[u8] {
+        &self.buf.as_ref()[self.pos..]
+    }
+}
+
+/// A chain of byte buffers with type B, read one after the other like an `IterChain`.
+///
+/// Buffers are dropped once they have been read, and are never copied together.
+/// [`chunks_vectored`](ByteChain::chunks_vectored) fills `IoSlice`s from several buffers, so
+/// they can be written with a single `write_vectored`. With the `bytes` feature, the chain also
+/// implements `bytes::Buf`.
+///
+/// ```
+/// use std::io::Read;
+///
+/// let body = b"body".to_vec();
+/// let mut frame = chaining_iter::ByteChain::new();
+/// frame.include(b"header:".to_vec());
+/// frame.include(body);
+///
+/// let mut s = String::new();
+/// frame.read_to_string(&mut s).unwrap();
+/// assert_eq!("header:body", s);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ByteChain<B = Vec<u8>> {
+    chunks: VecDeque<Chunk<B>>,
+    remaining: usize,
+}
+
+impl<B> ByteChain<B>
+where
+    B: ByteSegment,
+{
+    pub fn new() -> ByteChain<B> {
+        ByteChain {
+            chunks: VecDeque::new(),
+            remaining: 0,
+        }
+    }
+
+    /// Include the given buffer at the end of the chain. Empty buffers are dropped.
+    pub fn include(&mut self, new_buf: B) {
+        let len = new_buf.as_ref().len();
+        if len > 0 {
+            self.remaining += len;
+            self.chunks.push_back(Chunk {
+                buf: new_buf,
+                pos: 0,
+            });
+        }
+    }
+
+    /// Include the given buffer at the front of the chain. Empty buffers are dropped.
+    pub fn include_front(&mut self, new_buf: B) {
+        let len = new_buf.as_ref().len();
+        if len > 0 {
+            self.remaining += len;
+            self.chunks.push_front(Chunk {
+                buf: new_buf,
+                pos: 0,
+            });
+        }
+    }
+
+    /// The number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// The unread part of the first buffer. Only empty if the whole chain has been read.
+    pub fn chunk(&self) -> &[u8] {
+        self.chunks.front().map_or(&[], Chunk::rest)
+    }
+
+    /// Fill `dst` with the unread parts of as many buffers as fit, returning how many were
+    /// filled.
+    ///
+    /// ```
+    /// use std::io::{IoSlice, Write};
+    ///
+    /// let mut frame = chaining_iter::ByteChain::new();
+    /// frame.include(&b"head"[..]);
+    /// frame.include(&b"body"[..]);
+    ///
+    /// let mut slices = [IoSlice::new(&[]); 4];
+    /// let n = frame.chunks_vectored(&mut slices);
+    ///
+    /// let mut out = Vec::new();
+    /// out.write_vectored(&slices[..n]).unwrap();
+    /// assert_eq!(b"headbody", &out[..]);
+    /// ```
+    pub fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        let mut n = 0;
+        for (slot, chunk) in dst.iter_mut().zip(&self.chunks) {
+            *slot = IoSlice::new(chunk.rest());
+            n += 1;
+        }
+        n
+    }
+
+    /// Skip the next `cnt` bytes, dropping every buffer that is fully read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cnt` is greater than the number of bytes left.
+    pub fn advance(&mut self, mut cnt: usize) {
+        assert!(
+            cnt <= self.remaining,
+            "cannot advance past the end of the chain"
+        );
+        self.remaining -= cnt;
+        while cnt > 0 {
+            let chunk = match self.chunks.front_mut() {
+                Some(chunk) => chunk,
+                None => return,
+            };
+            let left = chunk.rest().len();
+            if cnt < left {
+                chunk.pos += cnt;
+                return;
+            }
+            cnt -= left;
+            self.chunks.pop_front();
+        }
+    }
+
+    /// Write as much of the chain as the writer takes in a single `write_vectored`, and skip past
+    /// it.
+    pub fn write_vectored_to<W>(&mut self, writer: &mut W) -> io::Result<usize>
+    where
+        W: Write,
+    {
+        const MAX_SLICES: usize = 64;
+
+        let n = {
+            let mut slices = [IoSlice::new(&[]); MAX_SLICES];
+            let filled = self.chunks_vectored(&mut slices);
+            writer.write_vectored(&slices[..filled])?
+        };
+        self.advance(n);
+        Ok(n)
+    }
+}
+
+impl<B> Default for ByteChain<B>
+where
+    B: ByteSegment,
+{
+    fn default() -> Self {
+        ByteChain::new()
+    }
+}
+
+impl<B> Read for ByteChain<B>
+where
+    B: ByteSegment,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let chunk = ByteChain::chunk(self);
+        let n = chunk.len().min(buf.len());
+        buf[..n].copy_from_slice(&chunk[..n]);
+        self.advance(n);
+        Ok(n)
+    }
+}
+
+impl<B> BufRead for ByteChain<B>
+where
+    B: ByteSegment,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(ByteChain::chunk(self))
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.advance(amt);
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<B> Buf for ByteChain<B>
+where
+    B: ByteSegment,
+{
+    fn remaining(&self) -> usize {
+        ByteChain::remaining(self)
+    }
+
+    fn chunk(&self) -> &[u8] {
+        ByteChain::chunk(self)
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        ByteChain::chunks_vectored(self, dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        ByteChain::advance(self, cnt)
+    }
+
+    /// Shares the memory of a `Bytes` buffer when all of the bytes come from it.
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        assert!(
+            len <= self.remaining,
+            "cannot copy past the end of the chain"
+        );
+        if let Some(chunk) = self.chunks.front() {
+            if len <= chunk.rest().len() {
+                let bytes = chunk.buf.slice_to_bytes(chunk.pos..chunk.pos + len);
+                self.advance(len);
+                return bytes;
+            }
+        }
+
+        let mut bytes = BytesMut::with_capacity(len);
+        bytes.put(Buf::take(&mut *self, len));
+        bytes.freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A writer that takes at most a few bytes per call, like a busy socket.
+    struct Trickle(Vec<u8>, usize);
+
+    impl Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.1);
+            self.0.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let mut c: ByteChain = ByteChain::new();
+        c.include(Vec::new());
+
+        assert_eq!(0, c.remaining());
+        assert!(c.chunk().is_empty());
+        assert_eq!(0, c.chunks_vectored(&mut [IoSlice::new(&[])]));
+    }
+
+    #[test]
+    fn advance_drops_read_buffers() {
+        let mut c = ByteChain::new();
+        c.include(&b"ab"[..]);
+        c.include(&b"cde"[..]);
+        c.include(&b"f"[..]);
+
+        c.advance(3);
+        assert_eq!(3, c.remaining());
+        assert_eq!(b"de", c.chunk());
+        assert_eq!(2, c.chunks.len());
+
+        c.include_front(&b"xy"[..]);
+        assert_eq!(b"xy", c.chunk());
+        c.advance(4);
+        assert_eq!(b"f", c.chunk());
+        c.advance(1);
+        assert!(c.chunks.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn advance_past_end() {
+        let mut c = ByteChain::new();
+        c.include(vec![1]);
+        c.advance(2);
+    }
+
+    #[test]
+    fn chunks_vectored_limited_by_dst() {
+        let mut c = ByteChain::new();
+        c.include(&b"ab"[..]);
+        c.include(&b"cd"[..]);
+        c.include(&b"ef"[..]);
+        c.advance(1);
+
+        let mut slices = [IoSlice::new(&[]); 2];
+        assert_eq!(2, c.chunks_vectored(&mut slices));
+        assert_eq!((&b"b"[..], &b"cd"[..]), (&*slices[0], &*slices[1]));
+    }
+
+    #[test]
+    fn write_to_trickling_sink() {
+        let mut c = ByteChain::new();
+        c.include(b"head".to_vec());
+        c.include(b"er".to_vec());
+        c.include(b"body".to_vec());
+
+        let mut sink = Trickle(Vec::new(), 3);
+        while c.remaining() > 0 {
+            c.write_vectored_to(&mut sink).unwrap();
+        }
+        assert_eq!(b"headerbody", &sink.0[..]);
+    }
+
+    #[test]
+    fn buf_read() {
+        let mut c = ByteChain::new();
+        c.include(&b"one\ntw"[..]);
+        c.include(&b"o\n"[..]);
+
+        let lines: Vec<_> = c.lines().map(Result::unwrap).collect();
+        assert_eq!(vec!["one", "two"], lines);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn copy_to_bytes_shares_memory() {
+        let body = Bytes::from_static(b"body");
+        let mut c = ByteChain::new();
+        c.include(Bytes::from_static(b"hd"));
+        c.include(body.clone());
+
+        assert_eq!(Bytes::from_static(b"hdb"), c.copy_to_bytes(3));
+        let rest = c.copy_to_bytes(3);
+        assert_eq!(b"ody", &rest[..]);
+        assert_eq!(body[1..].as_ptr(), rest.as_ptr());
+        assert_eq!(0, Buf::remaining(&c));
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn buf_over_vecs() {
+        let mut c = ByteChain::new();
+        c.include(vec![0, 1]);
+        c.include(vec![2, 3, 4, 5]);
+
+        assert_eq!(0x0001_0203, c.get_u32());
+        assert_eq!(vec![4, 5], c.copy_to_bytes(2).to_vec());
+        assert!(!c.has_remaining());
+    }
+}
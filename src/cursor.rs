@@ -0,0 +1,392 @@
+//! Positional access to the iterators pending in an `IterChain`.
+
+use std::collections::VecDeque;
+
+use crate::{IterChain, Segment, SegmentId};
+
+impl<I, M> IterChain<I, M> {
+    /// Move the cached front and back iterators into the deque, so every pending iterator can be
+    /// reached by its index.
+    fn flatten_ends(&mut self) -> &mut VecDeque<Segment<I, M>> {
+        if let Some(front) = self.front.take() {
+            self.iters.push_front(front);
+        }
+        if let Some(back) = self.back.take() {
+            self.iters.push_back(back);
+        }
+        &mut self.iters
+    }
+
+    /// A cursor pointing at the first pending iterator, or at the "ghost" position if the chain
+    /// is empty.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, I, M> {
+        self.flatten_ends();
+        CursorMut {
+            index: 0,
+            chain: self,
+        }
+    }
+
+    /// A cursor pointing at the last pending iterator, or at the "ghost" position if the chain
+    /// is empty.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, I, M> {
+        let len = self.flatten_ends().len();
+        CursorMut {
+            index: len.saturating_sub(1),
+            chain: self,
+        }
+    }
+
+    /// Include the given iterator with its metadata so it becomes the pending iterator at
+    /// `index`, returning its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of pending iterators.
+    pub fn include_at_with_meta(&mut self, index: usize, new_iter: I, meta: M) -> SegmentId
+    where
+        I: Iterator,
+    {
+        let segment = self.new_segment(new_iter, meta);
+        let id = segment.id;
+        self.flatten_ends().insert(index, segment);
+        id
+    }
+
+    /// Take the pending iterator at `index` out of the chain.
+    pub fn remove_at(&mut self, index: usize) -> Option<I> {
+        self.flatten_ends()
+            .remove(index)
+            .map(|segment| segment.iter)
+    }
+
+    /// Create an empty chain that will not hand out ids that are already used in this one.
+    fn split_empty(&self) -> IterChain<I, M> {
+        IterChain {
+            front: None,
+            iters: VecDeque::new(),
+            back: None,
+            next_id: self.next_id,
+        }
+    }
+}
+
+impl<I> IterChain<I> {
+    /// Include the given iterator so it becomes the pending iterator at `index`, returning its
+    /// id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of pending iterators.
+    ///
+    /// ```
+    /// let mut i = chaining_iter::IterChain::new();
+    /// i.include(0..2);
+    /// i.include(5..7);
+    /// i.include_at(1, 2..5);
+    ///
+    /// assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], i.collect::<Vec<_>>());
+    /// ```
+    pub fn include_at(&mut self, index: usize, new_iter: I) -> SegmentId
+    where
+        I: Iterator,
+    {
+        self.include_at_with_meta(index, new_iter, ())
+    }
+}
+
+/// A cursor over the iterators pending in an `IterChain`, modeled on
+/// `std::collections::linked_list::CursorMut`.
+///
+/// Past the last iterator and before the first one, the cursor points at a "ghost" position that
+/// has no iterator.
+///
+/// ```
+/// let mut i = chaining_iter::IterChain::new();
+/// i.include(0..2);
+/// i.include(10..12);
+///
+/// let mut cursor = i.cursor_front_mut();
+/// cursor.move_next();
+/// cursor.insert_before(5..7);
+/// assert_eq!(Some(&mut (10..12)), cursor.current());
+///
+/// assert_eq!(vec![0, 1, 5, 6, 10, 11], i.collect::<Vec<_>>());
+/// ```
+#[derive(Debug)]
+pub struct CursorMut<'a, I, M = ()> {
+    /// Points at the ghost position when it equals the number of pending iterators.
+    index: usize,
+    chain: &'a mut IterChain<I, M>,
+}
+
+impl<'a, I, M> CursorMut<'a, I, M> {
+    /// The index of the current iterator, or `None` at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        if self.index < self.chain.iters.len() {
+            Some(self.index)
+        } else {
+            None
+        }
+    }
+
+    /// Move to the next iterator. Moves from the last iterator to the ghost position, and from
+    /// the ghost position to the first iterator.
+    pub fn move_next(&mut self) {
+        if self.index < self.chain.iters.len() {
+            self.index += 1;
+        } else {
+            self.index = 0;
+        }
+    }
+
+    /// Move to the previous iterator. Moves from the first iterator to the ghost position, and
+    /// from the ghost position to the last iterator.
+    pub fn move_prev(&mut self) {
+        let len = self.chain.iters.len();
+        if self.index == 0 {
+            self.index = len;
+        } else {
+            self.index -= 1;
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut I> {
+        self.chain
+            .iters
+            .get_mut(self.index)
+            .map(|segment| &mut segment.iter)
+    }
+
+    pub fn current_meta(&mut self) -> Option<&mut M> {
+        self.chain
+            .iters
+            .get_mut(self.index)
+            .map(|segment| &mut segment.meta)
+    }
+
+    pub fn current_id(&self) -> Option<SegmentId> {
+        self.chain.iters.get(self.index).map(|segment| segment.id)
+    }
+
+    /// The iterator after the current one, without moving the cursor.
+    pub fn peek_next(&mut self) -> Option<&mut I> {
+        let next = if self.index < self.chain.iters.len() {
+            self.index + 1
+        } else {
+            0
+        };
+        self.chain
+            .iters
+            .get_mut(next)
+            .map(|segment| &mut segment.iter)
+    }
+
+    /// The iterator before the current one, without moving the cursor.
+    pub fn peek_prev(&mut self) -> Option<&mut I> {
+        let prev = self.index.checked_sub(1)?;
+        self.chain
+            .iters
+            .get_mut(prev)
+            .map(|segment| &mut segment.iter)
+    }
+
+    /// Include the given iterator with its metadata after the current one, returning its id. At
+    /// the ghost position it goes at the front of the chain.
+    pub fn insert_after_with_meta(&mut self, new_iter: I, meta: M) -> SegmentId
+    where
+        I: Iterator,
+    {
+        let segment = self.chain.new_segment(new_iter, meta);
+        let id = segment.id;
+        if self.index < self.chain.iters.len() {
+            self.chain.iters.insert(self.index + 1, segment);
+        } else {
+            self.chain.iters.push_front(segment);
+            self.index += 1;
+        }
+        id
+    }
+
+    /// Include the given iterator with its metadata before the current one, returning its id.
+    /// At the ghost position it goes at the back of the chain.
+    pub fn insert_before_with_meta(&mut self, new_iter: I, meta: M) -> SegmentId
+    where
+        I: Iterator,
+    {
+        let segment = self.chain.new_segment(new_iter, meta);
+        let id = segment.id;
+        self.chain.iters.insert(self.index, segment);
+        self.index += 1;
+        id
+    }
+
+    /// Take the current iterator out of the chain, and move to the next one.
+    pub fn remove_current(&mut self) -> Option<I> {
+        self.chain
+            .iters
+            .remove(self.index)
+            .map(|segment| segment.iter)
+    }
+
+    /// Split the chain after the current iterator, returning everything after it. At the ghost
+    /// position the whole chain is returned.
+    pub fn split_after(&mut self) -> IterChain<I, M> {
+        let mut rest = self.chain.split_empty();
+        if self.index < self.chain.iters.len() {
+            rest.iters = self.chain.iters.split_off(self.index + 1);
+        } else {
+            rest.iters = std::mem::take(&mut self.chain.iters);
+            self.index = 0;
+        }
+        rest
+    }
+
+    /// Split the chain before the current iterator, returning everything before it. At the ghost
+    /// position the whole chain is returned.
+    pub fn split_before(&mut self) -> IterChain<I, M> {
+        let mut before = self.chain.split_empty();
+        let rest = self.chain.iters.split_off(self.index);
+        before.iters = std::mem::replace(&mut self.chain.iters, rest);
+        self.index = 0;
+        before
+    }
+}
+
+impl<'a, I> CursorMut<'a, I> {
+    /// Include the given iterator after the current one, returning its id. At the ghost position
+    /// it goes at the front of the chain.
+    pub fn insert_after(&mut self, new_iter: I) -> SegmentId
+    where
+        I: Iterator,
+    {
+        self.insert_after_with_meta(new_iter, ())
+    }
+
+    /// Include the given iterator before the current one, returning its id. At the ghost
+    /// position it goes at the back of the chain.
+    pub fn insert_before(&mut self, new_iter: I) -> SegmentId
+    where
+        I: Iterator,
+    {
+        self.insert_before_with_meta(new_iter, ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walk_both_ways() {
+        let mut i = IterChain::new();
+        i.include(0..1);
+        i.include(1..2);
+
+        let mut cursor = i.cursor_front_mut();
+        assert_eq!(Some(0), cursor.index());
+        cursor.move_next();
+        assert_eq!(Some(&mut (1..2)), cursor.current());
+        cursor.move_next();
+        assert_eq!(None, cursor.index());
+        assert_eq!(None, cursor.current());
+        assert_eq!(Some(&mut (0..1)), cursor.peek_next());
+        cursor.move_prev();
+        assert_eq!(Some(1), cursor.index());
+        assert_eq!(Some(&mut (0..1)), cursor.peek_prev());
+    }
+
+    #[test]
+    fn cursor_after_partial_iteration() {
+        let mut i = IterChain::new();
+        i.include(0..3);
+        i.include(3..6);
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(5), i.next_back());
+
+        let mut cursor = i.cursor_back_mut();
+        assert_eq!(Some(&mut (3..5)), cursor.current());
+        cursor.insert_after(10..11);
+        cursor.move_prev();
+        assert_eq!(Some(&mut (1..3)), cursor.current());
+
+        assert_eq!(vec![1, 2, 3, 4, 10], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn insert_at_ghost() {
+        let mut i = IterChain::new();
+        i.include(5..6);
+
+        let mut cursor = i.cursor_back_mut();
+        cursor.move_next();
+        cursor.insert_after(0..1);
+        cursor.insert_before(9..10);
+        assert_eq!(None, cursor.index());
+
+        assert_eq!(vec![0, 5, 9], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn remove_and_mutate() {
+        let mut i = IterChain::new();
+        i.include_with_meta(0..2, 'a');
+        i.include_with_meta(2..4, 'b');
+        i.include_with_meta(4..6, 'c');
+
+        let mut cursor = i.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(Some(2..4), cursor.remove_current());
+        assert_eq!(Some(&mut 'c'), cursor.current_meta());
+        cursor.current().unwrap().next();
+
+        assert_eq!(vec![0, 1, 5], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn split() {
+        let mut i = IterChain::new();
+        i.include(0..1);
+        i.include(1..2);
+        i.include(2..3);
+        i.include(3..4);
+
+        let mut cursor = i.cursor_front_mut();
+        cursor.move_next();
+        let tail = cursor.split_after();
+        assert_eq!(Some(1), cursor.index());
+        let head = cursor.split_before();
+        assert_eq!(Some(0), cursor.index());
+
+        assert_eq!(vec![0], head.collect::<Vec<_>>());
+        assert_eq!(vec![2, 3], tail.collect::<Vec<_>>());
+        assert_eq!(vec![1], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn split_ids_stay_unique() {
+        let mut i = IterChain::new();
+        let a = i.include(0..1);
+        i.include(1..2);
+
+        let mut tail = i.cursor_front_mut().split_after();
+        let b = tail.include(2..3);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn include_and_remove_at() {
+        let mut i = IterChain::new();
+        i.include(0..1);
+        i.include(2..3);
+
+        assert_eq!(Some(0), i.next());
+        i.include_at(0, 10..11);
+        i.include_at(2, 1..2);
+        assert_eq!(Some(2..3), i.remove_at(3));
+        assert_eq!(None, i.remove_at(3));
+
+        assert_eq!(vec![10, 1], i.collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,339 @@
+//! Dependency ordered chains of iterators.
+
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::iter;
+
+use crate::{sum_size_hints, SegmentId};
+
+/// Why a dependency could not be added to a `DagChain`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DagError {
+    /// The id was not returned by this chain.
+    UnknownSegment(SegmentId),
+    /// The iterator has already started yielding items, so it can't wait on anything anymore.
+    AlreadyStarted(SegmentId),
+    /// The dependency would make the iterators wait on each other.
+    Cycle,
+}
+
+impl fmt::Display for DagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DagError::UnknownSegment(id) => write!(f, "unknown segment {:?}", id),
+            DagError::AlreadyStarted(id) => write!(f, "segment {:?} has already started", id),
+            DagError::Cycle => write!(f, "dependency would create a cycle"),
+        }
+    }
+}
+
+impl Error for DagError {}
+
+/// An iterator waiting in a `DagChain` until it is eligible and its turn comes.
+#[derive(Debug, Clone)]
+struct Node<I> {
+    iter: I,
+    /// The number of dependencies that are not exhausted yet.
+    waiting_on: usize,
+}
+
+/// A chain of iterators with type I, where each iterator can wait for others to be exhausted
+/// before it starts.
+///
+/// An iterator is eligible once all of its dependencies are exhausted. Eligible iterators are
+/// drained one at a time, in the order they were included.
+///
+/// ```
+/// let mut i = chaining_iter::DagChain::new();
+/// let link = i.include(20..22, None).unwrap();
+/// let compile = i.include(0..2, None).unwrap();
+/// let test = i.include(10..12, Some(compile)).unwrap();
+/// i.add_dependency(link, compile).unwrap();
+///
+/// assert_eq!(vec![0, 1, 20, 21, 10, 11], i.collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DagChain<I> {
+    /// The iterator being drained.
+    current: Option<(SegmentId, I)>,
+    /// Iterators that have not started yet.
+    nodes: HashMap<SegmentId, Node<I>>,
+    /// The iterators waiting on each iterator that is not exhausted yet.
+    dependents: HashMap<SegmentId, Vec<SegmentId>>,
+    /// Iterators that have not started yet, and are no longer waiting on anything.
+    ready: BTreeSet<SegmentId>,
+    next_id: u64,
+}
+
+impl<I> DagChain<I>
+where
+    I: Iterator,
+{
+    pub fn new() -> DagChain<I> {
+        DagChain {
+            current: None,
+            nodes: HashMap::new(),
+            dependents: HashMap::new(),
+            ready: BTreeSet::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Include the given iterator, to start once all of the given dependencies are exhausted.
+    /// Returns its id, or an error if one of the dependencies is unknown.
+    pub fn include<D>(&mut self, new_iter: I, dependencies: D) -> Result<SegmentId, DagError>
+    where
+        D: IntoIterator<Item = SegmentId>,
+    {
+        let dependencies: Vec<_> = dependencies.into_iter().collect();
+        if let Some(&unknown) = dependencies.iter().find(|id| id.0 >= self.next_id) {
+            return Err(DagError::UnknownSegment(unknown));
+        }
+
+        let id = SegmentId(self.next_id);
+        self.next_id += 1;
+        self.nodes.insert(
+            id,
+            Node {
+                iter: new_iter,
+                waiting_on: 0,
+            },
+        );
+        self.ready.insert(id);
+
+        for dependency in dependencies {
+            self.add_edge(id, dependency);
+        }
+        Ok(id)
+    }
+
+    /// Make `id` wait until `dependency` is exhausted.
+    ///
+    /// ```
+    /// use chaining_iter::{DagChain, DagError};
+    ///
+    /// let mut i = DagChain::new();
+    /// let a = i.include(0..2, None).unwrap();
+    /// let b = i.include(2..4, Some(a)).unwrap();
+    ///
+    /// assert_eq!(Err(DagError::Cycle), i.add_dependency(a, b));
+    /// ```
+    pub fn add_dependency(&mut self, id: SegmentId, dependency: SegmentId) -> Result<(), DagError> {
+        for check in [id, dependency].iter() {
+            if check.0 >= self.next_id {
+                return Err(DagError::UnknownSegment(*check));
+            }
+        }
+        if !self.nodes.contains_key(&id) {
+            return Err(DagError::AlreadyStarted(id));
+        }
+        if self.reaches(id, dependency) {
+            return Err(DagError::Cycle);
+        }
+
+        self.add_edge(id, dependency);
+        Ok(())
+    }
+
+    /// Check if `to` waits on `from`, directly or not, or they are the same iterator.
+    fn reaches(&self, from: SegmentId, to: SegmentId) -> bool {
+        let mut stack = vec![from];
+        let mut seen = BTreeSet::new();
+        while let Some(id) = stack.pop() {
+            if id == to {
+                return true;
+            }
+            if seen.insert(id) {
+                if let Some(dependents) = self.dependents.get(&id) {
+                    stack.extend(dependents);
+                }
+            }
+        }
+        false
+    }
+
+    fn add_edge(&mut self, id: SegmentId, dependency: SegmentId) {
+        let exhausted = dependency.0 < self.next_id
+            && !self.nodes.contains_key(&dependency)
+            && self.current.as_ref().map(|(current, _)| *current) != Some(dependency);
+        if exhausted {
+            return;
+        }
+
+        self.dependents.entry(dependency).or_default().push(id);
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.waiting_on += 1;
+        }
+        self.ready.remove(&id);
+    }
+
+    /// Mark the iterator as exhausted, so everything waiting on it can become eligible.
+    fn finish(&mut self, id: SegmentId) {
+        for dependent in self.dependents.remove(&id).unwrap_or_default() {
+            if let Some(node) = self.nodes.get_mut(&dependent) {
+                node.waiting_on -= 1;
+                if node.waiting_on == 0 {
+                    self.ready.insert(dependent);
+                }
+            }
+        }
+    }
+
+    /// Check if the iterator with the given id has not been exhausted yet.
+    pub fn contains(&self, id: SegmentId) -> bool {
+        self.nodes.contains_key(&id)
+            || self.current.as_ref().map(|(current, _)| *current) == Some(id)
+    }
+}
+
+impl<I> Default for DagChain<I>
+where
+    I: Iterator,
+{
+    fn default() -> Self {
+        DagChain::new()
+    }
+}
+
+impl<I> Iterator for DagChain<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((id, iter)) = &mut self.current {
+                let val = iter.next();
+                if val.is_some() {
+                    return val;
+                }
+                let id = *id;
+                self.current = None;
+                self.finish(id);
+            }
+
+            let id = *self.ready.iter().next()?;
+            self.ready.remove(&id);
+            let node = self.nodes.remove(&id)?;
+            self.current = Some((id, node.iter));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = sum_size_hints(
+            self.current
+                .iter()
+                .map(|(_, iter)| iter.size_hint())
+                .chain(self.nodes.values().map(|node| node.iter.size_hint())),
+        );
+        // Iterators that are still waiting can't start while nothing else is left.
+        if self.current.is_none() && self.ready.is_empty() {
+            return (0, Some(0));
+        }
+        (lower, upper)
+    }
+}
+
+impl<I> iter::Extend<I> for DagChain<I>
+where
+    I: Iterator,
+{
+    /// Include iterators without any dependencies.
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iters: T) {
+        for iter in iters {
+            // No dependencies can't fail.
+            let _ = self.include(iter, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut i: DagChain<Range<usize>> = DagChain::new();
+
+        assert_eq!(None, i.next());
+    }
+
+    #[test]
+    fn waits_for_all_dependencies() {
+        let mut i = DagChain::new();
+        let a = i.include(0..2, None).unwrap();
+        let b = i.include(2..4, None).unwrap();
+        i.include(10..11, vec![a, b]).unwrap();
+        i.include(4..5, None).unwrap();
+
+        assert_eq!(vec![0, 1, 2, 3, 10, 4], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn eligible_in_include_order() {
+        let mut i = DagChain::new();
+        let a = i.include(0..1, None).unwrap();
+        let b = i.include(10..11, Some(a)).unwrap();
+        i.include(20..21, None).unwrap();
+        i.include(30..31, Some(b)).unwrap();
+
+        assert_eq!(vec![0, 10, 20, 30], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn include_while_running() {
+        let mut i = DagChain::new();
+        let a = i.include(0..2, None).unwrap();
+
+        assert_eq!(Some(0), i.next());
+        let b = i.include(10..11, Some(a)).unwrap();
+        i.include(20..21, None).unwrap();
+        assert_eq!(Some(1), i.next());
+        assert!(i.contains(a));
+        assert_eq!(Some(10), i.next());
+        assert!(!i.contains(a));
+
+        // Depending on an exhausted iterator doesn't wait.
+        i.include(30..31, Some(a)).unwrap();
+        assert_eq!(Some(20), i.next());
+        assert!(!i.contains(b));
+        assert_eq!(vec![30], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn dependency_errors() {
+        let mut i = DagChain::new();
+        let a = i.include(0..1, None).unwrap();
+        let b = i.include(1..2, None).unwrap();
+        let c = i.include(2..3, Some(b)).unwrap();
+
+        assert_eq!(Err(DagError::Cycle), i.add_dependency(a, a));
+        assert_eq!(Ok(()), i.add_dependency(b, a));
+        assert_eq!(Err(DagError::Cycle), i.add_dependency(a, c));
+        assert_eq!(
+            Err(DagError::UnknownSegment(SegmentId(7))),
+            i.include(0..0, Some(SegmentId(7)))
+        );
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Err(DagError::AlreadyStarted(a)), i.add_dependency(a, c));
+        assert_eq!(vec![1, 2], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn size_hint_counts_blocked() {
+        let mut i = DagChain::new();
+        let a = i.include(0..2, None).unwrap();
+        i.include(2..4, Some(a)).unwrap();
+
+        // 2..4 is blocked until 0..2 finishes, but its items still count.
+        assert_eq!(Some(0), i.next());
+        assert_eq!((3, Some(3)), i.size_hint());
+        assert_eq!(Some(1), i.next());
+        assert_eq!((2, Some(2)), i.size_hint());
+    }
+}
@@ -0,0 +1,198 @@
+//! Streams that are only opened once a chain reaches them, enabled by the `futures` feature.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::future::{Future, FutureExt};
+use futures::ready;
+use futures::stream::{Stream, StreamExt};
+
+use crate::StreamChain;
+
+/// A stream with type S that comes from a future with type F, which is first polled when an item
+/// is taken from it. A stream that is already open can be wrapped with
+/// [`ready`](Deferred::ready), so one chain can hold both.
+///
+/// The size hint is unknown until the future resolves. A future that is never reached is
+/// dropped without being polled.
+///
+/// Like every stream in a `StreamChain`, F and S must be `Unpin`. Futures that are not, like
+/// `async` blocks, can be boxed with `FutureExt::boxed`, which also lets futures of different
+/// types be chained as a `BoxFuture`.
+#[derive(Debug, Clone)]
+pub struct Deferred<F, S> {
+    future: Option<F>,
+    stream: Option<S>,
+}
+
+impl<F, S> Deferred<F, S>
+where
+    F: Future<Output = S> + Unpin,
+    S: Stream + Unpin,
+{
+    pub fn new(future: F) -> Deferred<F, S> {
+        Deferred {
+            future: Some(future),
+            stream: None,
+        }
+    }
+
+    /// Wrap a stream that is already open.
+    pub fn ready(stream: S) -> Deferred<F, S> {
+        Deferred {
+            future: None,
+            stream: Some(stream),
+        }
+    }
+
+    /// Check if the future has resolved to a stream.
+    pub fn is_open(&self) -> bool {
+        self.future.is_none()
+    }
+}
+
+impl<F, S> Stream for Deferred<F, S>
+where
+    F: Future<Output = S> + Unpin,
+    S: Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(future) = self.future.as_mut() {
+            let stream = ready!(future.poll_unpin(cx));
+            self.future = None;
+            self.stream = Some(stream);
+        }
+        match self.stream.as_mut() {
+            Some(stream) => stream.poll_next_unpin(cx),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match (&self.future, &self.stream) {
+            (Some(_), _) => (0, None),
+            (None, Some(stream)) => stream.size_hint(),
+            (None, None) => (0, Some(0)),
+        }
+    }
+}
+
+impl<F, S> StreamChain<Deferred<F, S>>
+where
+    F: Future<Output = S> + Unpin,
+    S: Stream + Unpin,
+{
+    /// Include a stream at the end of the chain that comes from the given future. The future is
+    /// only polled once the chain reaches it.
+    ///
+    /// Futures that resolve to iterators can be mapped with `futures::stream::iter`.
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future::{BoxFuture, FutureExt};
+    /// use futures::stream::{self, StreamExt};
+    /// use chaining_iter::{Deferred, StreamChain};
+    ///
+    /// type Page = stream::Iter<std::vec::IntoIter<u32>>;
+    ///
+    /// let mut s: StreamChain<Deferred<BoxFuture<'static, Page>, Page>> = StreamChain::new();
+    /// for page in 1..3 {
+    ///     s.include_future(async move { stream::iter(vec![page * 10, page * 10 + 1]) }.boxed());
+    /// }
+    /// s.include_front(Deferred::ready(stream::iter(vec![0, 1])));
+    ///
+    /// assert_eq!(vec![0, 1, 10, 11, 20, 21], block_on(s.collect::<Vec<_>>()));
+    /// ```
+    pub fn include_future(&mut self, future: F) {
+        self.include(Deferred::new(future));
+    }
+
+    /// Include a stream at the front of the chain that comes from the given future. The future is
+    /// only polled once the chain reaches it.
+    pub fn include_front_future(&mut self, future: F) {
+        self.include_front(Deferred::new(future));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use futures::channel::oneshot;
+    use futures::executor::block_on;
+    use futures::future::{self, Ready};
+    use futures::stream;
+    use futures::task::waker;
+
+    use super::*;
+    use crate::test_util::CountWakes;
+
+    #[test]
+    fn futures_polled_when_reached() {
+        let polled = Arc::new(AtomicUsize::new(0));
+        let page = |n: usize| {
+            let polled = Arc::clone(&polled);
+            future::poll_fn(move |_| {
+                polled.fetch_add(1, Ordering::SeqCst);
+                Poll::Ready(stream::iter(n..n + 2))
+            })
+        };
+
+        let mut s = StreamChain::new();
+        s.include_future(page(10));
+        s.include_future(page(20));
+        s.include_front_future(page(0));
+        assert_eq!(0, polled.load(Ordering::SeqCst));
+
+        assert_eq!(Some(0), block_on(s.next()));
+        assert_eq!(1, polled.load(Ordering::SeqCst));
+        assert_eq!(Some(1), block_on(s.next()));
+        assert_eq!(Some(10), block_on(s.next()));
+        assert_eq!(2, polled.load(Ordering::SeqCst));
+
+        drop(s);
+        assert_eq!(2, polled.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn pending_future_wakes_chain() {
+        let wakes = Arc::new(CountWakes::default());
+        let waker = waker(Arc::clone(&wakes));
+        let mut cx = Context::from_waker(&waker);
+
+        let (tx, rx) = oneshot::channel();
+        let mut s = StreamChain::new();
+        s.include_future(rx.map(|page: Result<Vec<u8>, _>| stream::iter(page.unwrap())));
+        assert_eq!(Poll::Pending, s.poll_next_unpin(&mut cx));
+
+        tx.send(vec![1, 2]).unwrap();
+        assert_eq!(1, wakes.count());
+        assert_eq!(Poll::Ready(Some(1)), s.poll_next_unpin(&mut cx));
+    }
+
+    #[test]
+    fn open_and_deferred_streams() {
+        type Page = stream::Iter<std::ops::Range<usize>>;
+
+        let mut s: StreamChain<Deferred<Ready<Page>, Page>> = StreamChain::new();
+        s.include(Deferred::ready(stream::iter(0..2)));
+        s.include_future(future::ready(stream::iter(2..4)));
+        s.include(Deferred::ready(stream::iter(4..5)));
+
+        assert_eq!(vec![0, 1, 2, 3, 4], block_on(s.collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn size_hint_until_open() {
+        let mut d = Deferred::new(future::ready(stream::iter(0..3)));
+        assert_eq!((0, None), d.size_hint());
+        assert!(!d.is_open());
+
+        assert_eq!(Some(0), block_on(d.next()));
+        assert!(d.is_open());
+        assert_eq!((2, Some(2)), d.size_hint());
+    }
+}
@@ -0,0 +1,156 @@
+//! Round robin interleaving of a chain of iterators.
+
+use std::collections::VecDeque;
+
+use crate::sum_size_hints;
+
+/// A chain of iterators with type I that takes turns between them.
+///
+/// Up to `quantum` items are taken from the front iterator before it is moved to the back of the
+/// chain. Exhausted iterators are dropped.
+///
+/// ```
+/// let mut i = chaining_iter::InterleaveChain::new(2);
+/// i.include(0..5);
+/// i.include(10..12);
+///
+/// assert_eq!(vec![0, 1, 10, 11, 2, 3, 4], i.collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone)]
+pub struct InterleaveChain<I> {
+    iters: VecDeque<I>,
+    quantum: usize,
+    taken: usize,
+}
+
+impl<I> InterleaveChain<I> {
+    /// Create an empty chain that takes up to `quantum` items from each iterator per turn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quantum` is 0.
+    pub fn new(quantum: usize) -> InterleaveChain<I>
+    where
+        I: Iterator,
+    {
+        assert!(quantum > 0, "quantum must be at least 1");
+        InterleaveChain {
+            iters: VecDeque::new(),
+            quantum,
+            taken: 0,
+        }
+    }
+
+    /// Include the given iterator at the end of the chain, so it gets the last turn of the
+    /// current round.
+    pub fn include(&mut self, new_iter: I)
+    where
+        I: Iterator,
+    {
+        self.iters.push_back(new_iter);
+    }
+
+    /// Include the given iterator at the front of the chain, so it gets the next turn. The
+    /// iterator whose turn was interrupted gets a full turn after it.
+    pub fn include_front(&mut self, new_iter: I)
+    where
+        I: Iterator,
+    {
+        self.iters.push_front(new_iter);
+        self.taken = 0;
+    }
+}
+
+impl<I> Iterator for InterleaveChain<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let iter = self.iters.front_mut()?;
+            let val = iter.next();
+            if val.is_some() {
+                self.taken += 1;
+                if self.taken == self.quantum {
+                    self.iters.rotate_left(1);
+                    self.taken = 0;
+                }
+                return val;
+            } else {
+                self.iters.pop_front();
+                self.taken = 0;
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        sum_size_hints(self.iters.iter().map(Iterator::size_hint))
+    }
+}
+
+impl<I> ExactSizeIterator for InterleaveChain<I> where I: ExactSizeIterator {}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut i: InterleaveChain<Range<usize>> = InterleaveChain::new(1);
+
+        assert_eq!(None, i.next());
+    }
+
+    #[test]
+    fn quantum_of_one() {
+        let mut i = InterleaveChain::new(1);
+        i.include(0..3);
+        i.include(10..11);
+        i.include(20..22);
+
+        assert_eq!(vec![0, 10, 20, 1, 21, 2], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn drops_empty_iters() {
+        let mut i = InterleaveChain::new(2);
+        i.include(0..0);
+        i.include(0..3);
+        i.include(10..10);
+
+        assert_eq!(vec![0, 1, 2], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn include_mid_iteration() {
+        let mut i = InterleaveChain::new(2);
+        i.include(0..4);
+        i.include(10..12);
+
+        assert_eq!(Some(0), i.next());
+        i.include_front(20..21);
+        i.include(30..31);
+
+        assert_eq!(vec![20, 1, 2, 10, 11, 30, 3], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn size_hint() {
+        let mut i = InterleaveChain::new(3);
+        i.include(0..4);
+        i.include(10..12);
+        i.next();
+
+        assert_eq!(5, i.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_quantum() {
+        let _: InterleaveChain<Range<usize>> = InterleaveChain::new(0);
+    }
+}
@@ -0,0 +1,294 @@
+//! A chain of iterators registered under unique keys.
+
+use crate::IterChain;
+
+/// What a `KeyedChain` does when an iterator is included under a key that is already in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DuplicateKey {
+    /// Hand the new iterator back, and leave the chain as is.
+    #[default]
+    Reject,
+    /// Drop the pending iterators under the key, and put the new one in their place.
+    Replace,
+    /// Chain the new iterator onto the pending iterators under the key.
+    Append,
+}
+
+/// A chain of iterators with type I, each registered under a unique key K.
+///
+/// Each key holds its own `IterChain`, so that iterators can be appended to it with
+/// [`DuplicateKey::Append`]. Keys are looked up by scanning the pending iterators, and are
+/// released once their iterators are exhausted. An iterator counts as exhausted as soon as its
+/// size hint says it is empty, otherwise only once the chain has moved past it.
+///
+/// ```
+/// use chaining_iter::{DuplicateKey, KeyedChain};
+///
+/// let mut i = KeyedChain::new(DuplicateKey::Replace);
+/// i.include_keyed("a", 0..3).unwrap();
+/// i.include_keyed("b", 3..5).unwrap();
+/// i.include_keyed("a", 10..12).unwrap();
+///
+/// assert_eq!(vec![10, 11, 3, 4], i.collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyedChain<K, I> {
+    chain: IterChain<IterChain<I>, K>,
+    duplicates: DuplicateKey,
+}
+
+impl<K, I> KeyedChain<K, I>
+where
+    K: Eq,
+    I: Iterator,
+{
+    /// Create an empty chain that handles duplicate keys as given.
+    pub fn new(duplicates: DuplicateKey) -> KeyedChain<K, I> {
+        KeyedChain {
+            chain: IterChain::new(),
+            duplicates,
+        }
+    }
+
+    /// Include the given iterator under the key at the end of the chain. If the key is already in
+    /// the chain and duplicates are rejected, the key and iterator are handed back.
+    pub fn include_keyed(&mut self, key: K, new_iter: I) -> Result<(), (K, I)> {
+        let duplicates = self.duplicates;
+        match self.get_mut(&key) {
+            Some(existing) => match duplicates {
+                DuplicateKey::Reject => return Err((key, new_iter)),
+                DuplicateKey::Replace => *existing = single(new_iter),
+                DuplicateKey::Append => {
+                    existing.include(new_iter);
+                }
+            },
+            None => {
+                self.chain.include_with_meta(single(new_iter), key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Include the given iterator under the key at the front of the chain. If the key is already
+    /// in the chain and duplicates are rejected, the key and iterator are handed back.
+    ///
+    /// Appended duplicates go in front of the pending iterators under the key.
+    pub fn include_front_keyed(&mut self, key: K, new_iter: I) -> Result<(), (K, I)> {
+        let duplicates = self.duplicates;
+        match self.get_mut(&key) {
+            Some(existing) => match duplicates {
+                DuplicateKey::Reject => return Err((key, new_iter)),
+                DuplicateKey::Replace => *existing = single(new_iter),
+                DuplicateKey::Append => {
+                    existing.include_front(new_iter);
+                }
+            },
+            None => {
+                self.chain.include_front_with_meta(single(new_iter), key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if there are pending iterators under the key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.chain
+            .pending()
+            .any(|(k, iters)| k == key && !is_drained(iters))
+    }
+
+    /// The pending iterators under the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut IterChain<I>> {
+        self.release_drained(key);
+        self.chain
+            .segments_mut()
+            .find(|segment| segment.meta == *key)
+            .map(|segment| &mut segment.iter)
+    }
+
+    /// Take the pending iterators under the key out of the chain.
+    pub fn remove(&mut self, key: &K) -> Option<IterChain<I>> {
+        self.release_drained(key);
+        let id = self
+            .chain
+            .segments()
+            .find(|segment| segment.meta == *key)?
+            .id;
+        self.chain.remove(id)
+    }
+
+    /// Drop the iterators under the key if they are known to be exhausted, even though the chain
+    /// hasn't moved past them yet.
+    fn release_drained(&mut self, key: &K) {
+        let drained = self
+            .chain
+            .segments()
+            .find(|segment| segment.meta == *key)
+            .filter(|segment| is_drained(&segment.iter))
+            .map(|segment| segment.id);
+        if let Some(id) = drained {
+            self.chain.remove(id);
+        }
+    }
+
+    /// Swap the pending iterators under the key for the given iterator, keeping its place in the
+    /// chain. If the key isn't in the chain, the iterator is handed back.
+    pub fn replace(&mut self, key: &K, new_iter: I) -> Result<IterChain<I>, I> {
+        match self.get_mut(key) {
+            Some(existing) => Ok(std::mem::replace(existing, single(new_iter))),
+            None => Err(new_iter),
+        }
+    }
+}
+
+impl<K, I> Default for KeyedChain<K, I>
+where
+    K: Eq,
+    I: Iterator,
+{
+    fn default() -> Self {
+        KeyedChain::new(DuplicateKey::default())
+    }
+}
+
+fn is_drained<I>(iters: &IterChain<I>) -> bool
+where
+    I: Iterator,
+{
+    iters.size_hint().1 == Some(0)
+}
+
+fn single<I>(iter: I) -> IterChain<I>
+where
+    I: Iterator,
+{
+    let mut chain = IterChain::new();
+    chain.include(iter);
+    chain
+}
+
+impl<K, I> Iterator for KeyedChain<K, I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chain.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chain.size_hint()
+    }
+
+    fn fold<B, F>(self, init: B, f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.chain.fold(init, f)
+    }
+}
+
+impl<K, I> DoubleEndedIterator for KeyedChain<K, I>
+where
+    I: DoubleEndedIterator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chain.next_back()
+    }
+
+    fn rfold<B, F>(self, init: B, f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.chain.rfold(init, f)
+    }
+}
+
+impl<K, I> ExactSizeIterator for KeyedChain<K, I> where I: ExactSizeIterator {}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::*;
+
+    #[test]
+    fn reject_duplicate() {
+        let mut i = KeyedChain::default();
+        i.include_keyed(1, 0..2).unwrap();
+
+        assert_eq!(Err((1, 5..6)), i.include_keyed(1, 5..6));
+        assert_eq!(vec![0, 1], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn replace_keeps_place() {
+        let mut i = KeyedChain::new(DuplicateKey::Replace);
+        i.include_keyed(1, 0..2).unwrap();
+        i.include_keyed(2, 2..4).unwrap();
+        i.include_front_keyed(2, 10..11).unwrap();
+
+        assert_eq!(vec![0, 1, 10], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn append_to_existing() {
+        let mut i = KeyedChain::new(DuplicateKey::Append);
+        i.include_keyed(1, 0..2).unwrap();
+        i.include_keyed(2, 2..4).unwrap();
+        i.include_keyed(1, 10..11).unwrap();
+        i.include_front_keyed(2, 20..21).unwrap();
+
+        assert_eq!(6, i.len());
+        assert_eq!(vec![0, 1, 10, 20, 2, 3], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn lookup_and_remove() {
+        let mut i: KeyedChain<&str, Range<usize>> = KeyedChain::default();
+        i.include_keyed("a", 0..3).unwrap();
+        i.include_keyed("b", 2..4).unwrap();
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(1), i.get_mut(&"a").and_then(|a| a.next()));
+        assert_eq!(vec![2], i.remove(&"a").unwrap().collect::<Vec<_>>());
+        assert!(!i.contains_key(&"a"));
+        assert_eq!(None, i.remove(&"c"));
+
+        assert_eq!(Err(5..6), i.replace(&"a", 5..6).map(|_| ()));
+        assert_eq!(
+            vec![2, 3],
+            i.replace(&"b", 7..8).unwrap().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![7], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn key_released_once_exhausted() {
+        let mut i = KeyedChain::default();
+        i.include_keyed(1, 0..1).unwrap();
+        i.include_keyed(2, 1..2).unwrap();
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(1), i.next());
+        assert!(!i.contains_key(&1));
+        i.include_keyed(1, 5..6).unwrap();
+
+        assert_eq!(vec![5], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn key_released_after_last_item() {
+        let mut i = KeyedChain::default();
+        i.include_keyed(1, 0..1).unwrap();
+        i.include_keyed(2, 1..2).unwrap();
+
+        assert_eq!(Some(0), i.next());
+        assert!(!i.contains_key(&1));
+        assert!(i.get_mut(&1).is_none());
+        i.include_keyed(1, 5..6).unwrap();
+
+        assert_eq!(vec![1, 5], i.collect::<Vec<_>>());
+    }
+}
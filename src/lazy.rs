@@ -0,0 +1,188 @@
+//! Iterators that are only constructed once a chain reaches them.
+
+use std::iter::FusedIterator;
+
+use crate::{IterChain, SegmentId};
+
+/// An iterator that is constructed by calling a factory the first time an item is taken from it,
+/// from either end.
+///
+/// The size hint is unknown until the iterator is constructed. A factory that is never reached is
+/// dropped without being called. To chain factories with different types, use a boxed
+/// `FnOnce() -> I` as F.
+#[derive(Debug, Clone)]
+pub struct Lazy<F, I> {
+    factory: Option<F>,
+    iter: Option<I>,
+}
+
+impl<F, I> Lazy<F, I>
+where
+    F: FnOnce() -> I,
+    I: Iterator,
+{
+    pub fn new(factory: F) -> Lazy<F, I> {
+        Lazy {
+            factory: Some(factory),
+            iter: None,
+        }
+    }
+
+    /// Wrap an iterator that has already been constructed.
+    pub fn ready(iter: I) -> Lazy<F, I> {
+        Lazy {
+            factory: None,
+            iter: Some(iter),
+        }
+    }
+
+    /// Check if the factory has been called.
+    pub fn is_constructed(&self) -> bool {
+        self.factory.is_none()
+    }
+
+    fn force(&mut self) -> Option<&mut I> {
+        if let Some(factory) = self.factory.take() {
+            self.iter = Some(factory());
+        }
+        self.iter.as_mut()
+    }
+}
+
+impl<F, I> Iterator for Lazy<F, I>
+where
+    F: FnOnce() -> I,
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.force()?.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match (&self.factory, &self.iter) {
+            (Some(_), _) => (0, None),
+            (None, Some(iter)) => iter.size_hint(),
+            (None, None) => (0, Some(0)),
+        }
+    }
+
+    fn fold<B, G>(mut self, init: B, f: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        match self.force() {
+            Some(_) => self.iter.into_iter().flatten().fold(init, f),
+            None => init,
+        }
+    }
+}
+
+impl<F, I> DoubleEndedIterator for Lazy<F, I>
+where
+    F: FnOnce() -> I,
+    I: DoubleEndedIterator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.force()?.next_back()
+    }
+}
+
+impl<F, I> FusedIterator for Lazy<F, I>
+where
+    F: FnOnce() -> I,
+    I: FusedIterator,
+{
+}
+
+impl<F, I> IterChain<Lazy<F, I>>
+where
+    F: FnOnce() -> I,
+    I: Iterator,
+{
+    /// Include an iterator at the end of the chain that is only constructed by calling the
+    /// factory once the chain reaches it, returning its id.
+    ///
+    /// ```
+    /// let mut i = chaining_iter::IterChain::new();
+    /// for n in 0..3 {
+    ///     i.include_lazy(move || n * 10..n * 10 + 2);
+    /// }
+    ///
+    /// assert_eq!(vec![0, 1, 10, 11, 20, 21], i.collect::<Vec<_>>());
+    /// ```
+    pub fn include_lazy(&mut self, factory: F) -> SegmentId {
+        self.include(Lazy::new(factory))
+    }
+
+    /// Include an iterator at the front of the chain that is only constructed by calling the
+    /// factory once the chain reaches it, returning its id.
+    pub fn include_front_lazy(&mut self, factory: F) -> SegmentId {
+        self.include_front(Lazy::new(factory))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn constructed_when_reached() {
+        let calls = Cell::new(0);
+        let make = |n: usize| {
+            let calls = &calls;
+            move || {
+                calls.set(calls.get() + 1);
+                n..n + 2
+            }
+        };
+
+        let mut i = IterChain::new();
+        i.include_lazy(make(0));
+        i.include_lazy(make(10));
+        i.include_lazy(make(20));
+        assert_eq!(0, calls.get());
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(1, calls.get());
+        assert_eq!(Some(21), i.next_back());
+        assert_eq!(2, calls.get());
+
+        drop(i);
+        assert_eq!(2, calls.get());
+    }
+
+    #[test]
+    fn unreached_factories_dropped() {
+        let mut i = IterChain::new();
+        i.include_lazy(|| -> std::ops::Range<usize> { panic!("should not be called") });
+        i.include_front(Lazy::ready(0..2));
+
+        assert_eq!(Some(0), i.next());
+    }
+
+    #[test]
+    fn boxed_factories() {
+        type Factory = Box<dyn FnOnce() -> std::vec::IntoIter<u8>>;
+
+        let mut i: IterChain<Lazy<Factory, _>> = IterChain::new();
+        i.include_lazy(Box::new(|| vec![1, 2].into_iter()));
+        i.include_front_lazy(Box::new(|| vec![0].into_iter()));
+
+        assert_eq!(3, i.count());
+    }
+
+    #[test]
+    fn size_hint_until_constructed() {
+        let mut l = Lazy::new(|| 0..3);
+        assert_eq!((0, None), l.size_hint());
+        assert!(!l.is_constructed());
+
+        assert_eq!(Some(0), l.next());
+        assert!(l.is_constructed());
+        assert_eq!((2, Some(2)), l.size_hint());
+    }
+}
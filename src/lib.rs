@@ -1,90 +1,559 @@
 //! A chaining iterator. It allows you to chain arbitrary number of same type iterators at run time.
 
+use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
 
-/// A chain of iterators with type I.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct IterChain<I> {
-    iters: VecDeque<I>,
+mod byte_chain;
+mod cursor;
+mod dag;
+#[cfg(feature = "futures")]
+mod deferred;
+mod interleave;
+mod keyed;
+mod lazy;
+mod merge;
+mod open;
+mod priority;
+mod read;
+mod seek;
+#[cfg(feature = "futures")]
+mod select;
+mod shared;
+mod slices;
+mod source;
+#[cfg(feature = "futures")]
+mod stream;
+mod tagged;
+#[cfg(all(test, feature = "futures"))]
+mod test_util;
+mod traverse;
+mod write;
+
+pub use byte_chain::{ByteChain, ByteSegment};
+pub use cursor::CursorMut;
+pub use dag::{DagChain, DagError};
+#[cfg(feature = "futures")]
+pub use deferred::Deferred;
+pub use interleave::InterleaveChain;
+pub use keyed::{DuplicateKey, KeyedChain};
+pub use lazy::Lazy;
+pub use merge::{KeyOrder, MergeChain, MergeOrder, NaturalOrder};
+pub use open::OpenChain;
+pub use priority::PriorityChain;
+pub use read::ReadChain;
+pub use seek::SeekChain;
+#[cfg(feature = "futures")]
+pub use select::SelectChain;
+pub use shared::{ChainProducer, SharedIterChain, TryNextError};
+pub use slices::SliceIter;
+pub use source::FromSource;
+#[cfg(feature = "futures")]
+pub use stream::StreamChain;
+pub use tagged::{Tagged, WithMeta};
+pub use traverse::{Traverse, Visit, VisitAll};
+pub use write::WriteChain;
+
+/// Identifies an iterator included in an `IterChain`.
+///
+/// Ids are unique within the chain that returned them and don't change when other iterators are
+/// included at either end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SegmentId(u64);
+
+/// An iterator included in an `IterChain`, along with its metadata and bookkeeping.
+#[derive(Debug, Clone)]
+struct Segment<I, M> {
+    id: SegmentId,
+    /// The number of items taken from the front of the iterator.
+    taken: usize,
+    meta: M,
+    iter: I,
+}
+
+/// A chain of iterators with type I, each carrying metadata with type M.
+///
+/// The iterators currently being polled from the front and from the back are kept outside of
+/// the `VecDeque`, so the deque is only touched when one of them is exhausted.
+///
+/// The chain is not a `FusedIterator`. That trait promises `None` forever once it has been
+/// returned, but including another iterator after that makes the chain yield items again. The
+/// same goes for every chain in this crate that can be extended while it is iterated.
+#[derive(Debug, Clone)]
+pub struct IterChain<I, M = ()> {
+    front: Option<Segment<I, M>>,
+    iters: VecDeque<Segment<I, M>>,
+    back: Option<Segment<I, M>>,
+    next_id: u64,
 }
 
 impl<I> IterChain<I> {
-    pub fn new() -> IterChain<I>
+    /// Include the given iterator at the end of the chain, returning its id.
+    pub fn include(&mut self, new_iter: I) -> SegmentId
+    where
+        I: Iterator,
+    {
+        self.include_with_meta(new_iter, ())
+    }
+
+    /// Include the given iterator at the front of the chain, returning its id.
+    ///
+    /// ```
+    /// let mut i = chaining_iter::IterChain::new();
+    /// i.include(3..5);
+    /// i.include_front(0..3);
+    ///
+    /// assert_eq!(Some(0), i.next());
+    /// ```
+    pub fn include_front(&mut self, new_iter: I) -> SegmentId
+    where
+        I: Iterator,
+    {
+        self.include_front_with_meta(new_iter, ())
+    }
+}
+
+impl<I, M> IterChain<I, M> {
+    pub fn new() -> IterChain<I, M>
     where
         I: Iterator,
     {
         IterChain {
+            front: None,
             iters: VecDeque::new(),
+            back: None,
+            next_id: 0,
         }
     }
 
-    /// Include the given iterator at the end of the chain.
-    pub fn include(&mut self, new_iter: I)
+    /// Include the given iterator with its metadata at the end of the chain, returning its id.
+    pub fn include_with_meta(&mut self, new_iter: I, meta: M) -> SegmentId
     where
         I: Iterator,
     {
-        self.iters.push_back(new_iter);
+        if let Some(back) = self.back.take() {
+            self.iters.push_back(back);
+        }
+        let segment = self.new_segment(new_iter, meta);
+        let id = segment.id;
+        self.iters.push_back(segment);
+        id
     }
 
-    /// Include the given iterator at the front of the chain.
+    /// Include the given iterator with its metadata at the front of the chain, returning its id.
+    pub fn include_front_with_meta(&mut self, new_iter: I, meta: M) -> SegmentId
+    where
+        I: Iterator,
+    {
+        if let Some(front) = self.front.take() {
+            self.iters.push_front(front);
+        }
+        let segment = self.new_segment(new_iter, meta);
+        let id = segment.id;
+        self.iters.push_front(segment);
+        id
+    }
+
+    /// The metadata of the front-most pending iterator that may still have items.
+    ///
+    /// Iterators whose size hint says they are empty are skipped. Others may turn out to be
+    /// exhausted when the next item is taken, in which case it comes from a later iterator.
     ///
     /// ```
     /// let mut i = chaining_iter::IterChain::new();
-    /// i.include(3..5);
-    /// i.include_front(0..3);
+    /// i.include_with_meta(0..1, "first");
+    /// i.include_with_meta(1..2, "second");
     ///
-    /// assert_eq!(Some(0), i.next());
+    /// assert_eq!(Some(&"first"), i.current_meta());
+    /// i.next();
+    /// assert_eq!(Some(&"second"), i.current_meta());
     /// ```
-    pub fn include_front(&mut self, new_iter: I)
+    pub fn current_meta(&self) -> Option<&M>
+    where
+        I: Iterator,
+    {
+        self.segments()
+            .find(|segment| segment.iter.size_hint().1 != Some(0))
+            .map(|segment| &segment.meta)
+    }
+
+    /// All of the iterators still in the chain with their metadata, from front to back.
+    pub fn pending(&self) -> impl DoubleEndedIterator<Item = (&M, &I)> {
+        self.segments()
+            .map(|segment| (&segment.meta, &segment.iter))
+    }
+
+    /// Check if the iterator with the given id is still in the chain.
+    pub fn contains(&self, id: SegmentId) -> bool {
+        self.segments().any(|segment| segment.id == id)
+    }
+
+    /// Take the iterator with the given id out of the chain, if it is still in it. It is
+    /// returned as is, so any items already taken from it are gone.
+    ///
+    /// ```
+    /// let mut i = chaining_iter::IterChain::new();
+    /// i.include(0..2);
+    /// let id = i.include(2..5);
+    ///
+    /// assert_eq!(Some(2..5), i.remove(id));
+    /// assert_eq!(vec![0, 1], i.collect::<Vec<_>>());
+    /// ```
+    pub fn remove(&mut self, id: SegmentId) -> Option<I> {
+        if self.front.as_ref().is_some_and(|segment| segment.id == id) {
+            return self.front.take().map(|segment| segment.iter);
+        }
+        if self.back.as_ref().is_some_and(|segment| segment.id == id) {
+            return self.back.take().map(|segment| segment.iter);
+        }
+        let index = self.iters.iter().position(|segment| segment.id == id)?;
+        self.iters.remove(index).map(|segment| segment.iter)
+    }
+
+    fn new_segment(&mut self, iter: I, meta: M) -> Segment<I, M> {
+        let id = SegmentId(self.next_id);
+        self.next_id += 1;
+        Segment {
+            id,
+            taken: 0,
+            meta,
+            iter,
+        }
+    }
+
+    /// All of the iterators still in the chain, from front to back.
+    fn segments(&self) -> impl DoubleEndedIterator<Item = &Segment<I, M>> {
+        self.front
+            .iter()
+            .chain(self.iters.iter())
+            .chain(self.back.iter())
+    }
+
+    fn segments_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Segment<I, M>> {
+        self.front
+            .iter_mut()
+            .chain(self.iters.iter_mut())
+            .chain(self.back.iter_mut())
+    }
+
+    /// Take all of the iterators still in the chain, from front to back.
+    fn into_segments(self) -> impl DoubleEndedIterator<Item = I> {
+        self.front
+            .into_iter()
+            .chain(self.iters)
+            .chain(self.back)
+            .map(|segment| segment.iter)
+    }
+
+    /// Refill the front iterator from the deque. Once the deque is empty the back iterator is
+    /// polled in place, so both ends can meet in the same iterator.
+    #[cold]
+    fn next_slow(&mut self) -> Option<I::Item>
     where
         I: Iterator,
     {
-        self.iters.push_front(new_iter);
+        self.front = None;
+        while let Some(mut segment) = self.iters.pop_front() {
+            let val = segment.iter.next();
+            if val.is_some() {
+                segment.taken += 1;
+                self.front = Some(segment);
+                return val;
+            }
+        }
+
+        let back = self.back.as_mut()?;
+        let val = back.iter.next();
+        if val.is_some() {
+            back.taken += 1;
+        } else {
+            self.back = None;
+        }
+        val
+    }
+
+    /// Refill the back iterator from the deque. Once the deque is empty the front iterator is
+    /// polled in place, so both ends can meet in the same iterator.
+    #[cold]
+    fn next_back_slow(&mut self) -> Option<I::Item>
+    where
+        I: DoubleEndedIterator,
+    {
+        self.back = None;
+        while let Some(mut segment) = self.iters.pop_back() {
+            let val = segment.iter.next_back();
+            if val.is_some() {
+                self.back = Some(segment);
+                return val;
+            }
+        }
+
+        let val = self.front.as_mut()?.iter.next_back();
+        if val.is_none() {
+            self.front = None;
+        }
+        val
     }
 }
 
-impl<I> Iterator for IterChain<I>
+impl<I, M> Default for IterChain<I, M>
+where
+    I: Iterator,
+{
+    fn default() -> Self {
+        IterChain::new()
+    }
+}
+
+// Comparisons and hashing only look at the iterators in the chain and their metadata, not at
+// which of them are currently cached at either end.
+impl<I, M> PartialEq for IterChain<I, M>
+where
+    I: PartialEq,
+    M: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.pending().eq(other.pending())
+    }
+}
+
+impl<I, M> Eq for IterChain<I, M>
+where
+    I: Eq,
+    M: Eq,
+{
+}
+
+impl<I, M> PartialOrd for IterChain<I, M>
+where
+    I: PartialOrd,
+    M: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.pending().partial_cmp(other.pending())
+    }
+}
+
+impl<I, M> Ord for IterChain<I, M>
+where
+    I: Ord,
+    M: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.pending().cmp(other.pending())
+    }
+}
+
+impl<I, M> Hash for IterChain<I, M>
+where
+    I: Hash,
+    M: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.segments().count().hash(state);
+        self.pending().for_each(|segment| segment.hash(state));
+    }
+}
+
+impl<I, M> Iterator for IterChain<I, M>
 where
     I: Iterator,
 {
     type Item = I::Item;
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if let Some(iter) = self.iters.front_mut() {
-                let val = iter.next();
-                if val.is_some() {
-                    return val;
-                } else {
-                    self.iters.pop_front();
+        if let Some(front) = &mut self.front {
+            let val = front.iter.next();
+            if val.is_some() {
+                front.taken += 1;
+                return val;
+            }
+        }
+        self.next_slow()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        sum_size_hints(self.segments().map(|segment| segment.iter.size_hint()))
+    }
+
+    // `try_fold` and `try_rfold` are not overridden as their signatures depend on the unstable
+    // `Try` trait. The methods below hand each whole iterator to its own implementation instead.
+
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.into_segments()
+            .fold(init, |acc, iter| iter.fold(acc, &mut f))
+    }
+
+    fn count(self) -> usize {
+        self.into_segments().map(Iterator::count).sum()
+    }
+
+    fn last(self) -> Option<Self::Item> {
+        self.into_segments().rev().find_map(Iterator::last)
+    }
+
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        if let Some(front) = &mut self.front {
+            match nth_in(&mut front.iter, n) {
+                Ok(val) => {
+                    front.taken += n + 1;
+                    return Some(val);
                 }
-            } else {
-                return None;
+                Err(rest) => n = rest,
             }
+            self.front = None;
         }
+        while let Some(mut segment) = self.iters.pop_front() {
+            match nth_in(&mut segment.iter, n) {
+                Ok(val) => {
+                    segment.taken += n + 1;
+                    self.front = Some(segment);
+                    return Some(val);
+                }
+                Err(rest) => n = rest,
+            }
+        }
+        if let Some(back) = &mut self.back {
+            if let Ok(val) = nth_in(&mut back.iter, n) {
+                back.taken += n + 1;
+                return Some(val);
+            }
+            self.back = None;
+        }
+        None
     }
 }
 
-impl<I> DoubleEndedIterator for IterChain<I>
+impl<I, M> DoubleEndedIterator for IterChain<I, M>
 where
     I: DoubleEndedIterator,
 {
+    #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
-        loop {
-            if let Some(iter) = self.iters.back_mut() {
-                let val = iter.next_back();
-                if val.is_some() {
-                    return val;
-                } else {
-                    self.iters.pop_back();
+        if let Some(back) = &mut self.back {
+            let val = back.iter.next_back();
+            if val.is_some() {
+                return val;
+            }
+        }
+        self.next_back_slow()
+    }
+
+    fn rfold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.into_segments()
+            .rev()
+            .fold(init, |acc, iter| iter.rfold(acc, &mut f))
+    }
+
+    fn nth_back(&mut self, mut n: usize) -> Option<Self::Item> {
+        if let Some(back) = &mut self.back {
+            match nth_back_in(&mut back.iter, n) {
+                Ok(val) => return Some(val),
+                Err(rest) => n = rest,
+            }
+            self.back = None;
+        }
+        while let Some(mut segment) = self.iters.pop_back() {
+            match nth_back_in(&mut segment.iter, n) {
+                Ok(val) => {
+                    self.back = Some(segment);
+                    return Some(val);
                 }
-            } else {
-                return None;
+                Err(rest) => n = rest,
             }
         }
+        if let Some(front) = &mut self.front {
+            if let Ok(val) = nth_back_in(&mut front.iter, n) {
+                return Some(val);
+            }
+            self.front = None;
+        }
+        None
+    }
+}
+
+/// Add up the size hints of several iterators, saturating the lower bound and dropping the upper
+/// bound if it overflows.
+fn sum_size_hints<H>(hints: H) -> (usize, Option<usize>)
+where
+    H: Iterator<Item = (usize, Option<usize>)>,
+{
+    hints.fold((0, Some(0)), |(lower, upper), (l, u)| {
+        let upper = match (upper, u) {
+            (Some(upper), Some(u)) => upper.checked_add(u),
+            _ => None,
+        };
+        (lower.saturating_add(l), upper)
+    })
+}
+
+/// The number of items left in the iterator, if its size hint is exact.
+fn exact_len<I>(iter: &I) -> Option<usize>
+where
+    I: Iterator,
+{
+    match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => Some(lower),
+        _ => None,
+    }
+}
+
+/// Take the nth item of a single iterator. If it runs out first, returns how many items are
+/// still left to skip, and the iterator should be dropped from the chain.
+fn nth_in<I>(iter: &mut I, mut n: usize) -> Result<I::Item, usize>
+where
+    I: Iterator,
+{
+    match exact_len(iter) {
+        // Skip the whole iterator without touching its items.
+        Some(len) if n >= len => Err(n - len),
+        Some(_) => iter.nth(n).ok_or(n),
+        None => {
+            for val in iter {
+                if n == 0 {
+                    return Ok(val);
+                }
+                n -= 1;
+            }
+            Err(n)
+        }
     }
 }
 
+/// Like `nth_in`, but counting from the back of the iterator.
+fn nth_back_in<I>(iter: &mut I, mut n: usize) -> Result<I::Item, usize>
+where
+    I: DoubleEndedIterator,
+{
+    match exact_len(iter) {
+        // Skip the whole iterator without touching its items.
+        Some(len) if n >= len => Err(n - len),
+        Some(_) => iter.nth_back(n).ok_or(n),
+        None => {
+            while let Some(val) = iter.next_back() {
+                if n == 0 {
+                    return Ok(val);
+                }
+                n -= 1;
+            }
+            Err(n)
+        }
+    }
+}
+
+/// # Panics
+///
+/// `len` panics if the lengths of the iterators in the chain add up to more than `usize::MAX`,
+/// since the size hint then has no upper bound.
+impl<I, M> ExactSizeIterator for IterChain<I, M> where I: ExactSizeIterator {}
+
 #[cfg(test)]
 mod tests {
     use std::ops::Range;
@@ -139,4 +608,210 @@ mod tests {
         assert_eq!(Some(0), i.next());
         assert_eq!(Some(6), i.next_back());
     }
+
+    #[test]
+    fn size_hint_sums_all_iters() {
+        let mut i = IterChain::new();
+        i.include(0..3);
+        i.include(5..7);
+
+        assert_eq!((5, Some(5)), i.size_hint());
+        assert_eq!(5, i.len());
+
+        i.next();
+        i.next_back();
+        assert_eq!(3, i.len());
+    }
+
+    #[test]
+    fn size_hint_upper_overflow() {
+        let mut i = IterChain::new();
+        i.include(0..usize::MAX);
+        i.include(0..2);
+
+        assert_eq!((usize::MAX, None), i.size_hint());
+    }
+
+    #[test]
+    #[should_panic]
+    fn len_overflow() {
+        let mut i = IterChain::new();
+        i.include(0..usize::MAX);
+        i.include(0..2);
+
+        i.len();
+    }
+
+    #[test]
+    fn include_after_exhausted() {
+        let mut i = IterChain::new();
+        i.include(0..1);
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(None, i.next());
+        i.include(5..6);
+        assert_eq!(Some(5), i.next());
+    }
+
+    #[test]
+    fn size_hint_unbounded() {
+        let mut i: IterChain<Box<dyn Iterator<Item = usize>>> = IterChain::new();
+        i.include(Box::new(0..2));
+        i.include(Box::new(0..));
+
+        assert_eq!((usize::MAX, None), i.size_hint());
+    }
+
+    #[test]
+    fn fold_in_order() {
+        let mut i = IterChain::new();
+        i.include(0..3);
+        i.include(3..3);
+        i.include(3..5);
+
+        assert_eq!(vec![0, 1, 2, 3, 4], i.clone().collect::<Vec<_>>());
+        assert_eq!(vec![4, 3, 2, 1, 0], i.clone().rev().collect::<Vec<_>>());
+        assert_eq!(10, i.clone().sum::<usize>());
+        assert_eq!(5, i.clone().count());
+        assert_eq!(Some(4), i.last());
+    }
+
+    #[test]
+    fn last_skips_empty_tail() {
+        let mut i = IterChain::new();
+        i.include(0..3);
+        i.include(3..3);
+
+        assert_eq!(Some(2), i.last());
+    }
+
+    #[test]
+    fn nth_across_iters() {
+        let mut i = IterChain::new();
+        i.include(0..3);
+        i.include(3..3);
+        i.include(3..6);
+
+        assert_eq!(Some(4), i.nth(4));
+        assert_eq!(Some(5), i.next());
+        assert_eq!(None, i.nth(1));
+    }
+
+    #[test]
+    fn nth_unknown_len() {
+        fn keep(_: &usize) -> bool {
+            true
+        }
+
+        let mut i = IterChain::new();
+        i.include((0..3).filter(keep as fn(&usize) -> bool));
+        i.include((3..6).filter(keep));
+
+        assert_eq!(Some(3), i.nth(3));
+        assert_eq!(Some(4), i.next());
+        assert_eq!(None, i.nth(1));
+    }
+
+    #[test]
+    fn nth_back_across_iters() {
+        let mut i = IterChain::new();
+        i.include(0..3);
+        i.include(3..3);
+        i.include(3..6);
+
+        assert_eq!(Some(1), i.nth_back(4));
+        assert_eq!(Some(0), i.next_back());
+        assert_eq!(None, i.nth_back(0));
+    }
+
+    #[test]
+    fn front_and_back_meet() {
+        let mut i = IterChain::new();
+        i.include(0..4);
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(3), i.next_back());
+        assert_eq!(Some(1), i.next());
+        assert_eq!(Some(2), i.next_back());
+        assert_eq!(None, i.next());
+        assert_eq!(None, i.next_back());
+    }
+
+    #[test]
+    fn include_while_iterating() {
+        let mut i = IterChain::new();
+        i.include(0..2);
+        i.include(2..4);
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(3), i.next_back());
+        i.include(4..6);
+        i.include_front(10..12);
+
+        assert_eq!(vec![10, 11, 1, 2, 4, 5], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn eq_ignores_cached_ends() {
+        let mut a = IterChain::new();
+        a.include(0..2);
+        a.include(2..4);
+        a.next();
+
+        let mut b = IterChain::new();
+        b.include(1..2);
+        b.include(2..4);
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn remove_partially_consumed() {
+        let mut i = IterChain::new();
+        let a = i.include(0..3);
+        let b = i.include(3..6);
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(5), i.next_back());
+        assert!(i.contains(a));
+        assert_eq!(Some(1..3), i.remove(a));
+        assert!(!i.contains(a));
+        assert_eq!(None, i.remove(a));
+
+        assert_eq!(Some(3..5), i.remove(b));
+        assert_eq!(None, i.next());
+    }
+
+    #[test]
+    fn contains_after_exhausted() {
+        let mut i = IterChain::new();
+        let a = i.include(0..1);
+        let b = i.include(1..2);
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(1), i.next());
+        assert!(!i.contains(a));
+        assert!(i.contains(b));
+        assert_eq!(None, i.next());
+        assert!(!i.contains(b));
+    }
+
+    #[test]
+    fn pending_with_meta() {
+        let mut i = IterChain::new();
+        i.include_with_meta(0..2, "a");
+        i.include_with_meta(2..4, "b");
+        i.include_front_with_meta(4..4, "c");
+
+        assert_eq!(Some(&"a"), i.current_meta());
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(&"a"), i.current_meta());
+        assert_eq!(Some(1), i.next());
+        assert_eq!(Some(&"b"), i.current_meta());
+        assert_eq!(Some(3), i.next_back());
+        assert_eq!(
+            vec![(&"a", &(2..2)), (&"b", &(2..3))],
+            i.pending().collect::<Vec<_>>()
+        );
+    }
 }
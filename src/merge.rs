@@ -0,0 +1,376 @@
+//! K-way merge of a chain of sorted iterators.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::sum_size_hints;
+
+/// An ordering used by a `MergeChain` to pick the next item.
+pub trait MergeOrder<T> {
+    fn cmp(&mut self, a: &T, b: &T) -> Ordering;
+}
+
+/// Merge items by their `Ord` implementation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NaturalOrder;
+
+impl<T> MergeOrder<T> for NaturalOrder
+where
+    T: Ord,
+{
+    fn cmp(&mut self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// Merge items by comparing the keys returned from the wrapped function.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyOrder<F>(F);
+
+impl<T, K, F> MergeOrder<T> for KeyOrder<F>
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    fn cmp(&mut self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+impl<T, F> MergeOrder<T> for F
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn cmp(&mut self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// The next item of an included iterator, waiting in the heap.
+struct Head<I>
+where
+    I: Iterator,
+{
+    item: I::Item,
+    /// Breaks ties between equal items, lower goes first.
+    seq: i64,
+    iter: I,
+}
+
+impl<I> Clone for Head<I>
+where
+    I: Iterator + Clone,
+    I::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        Head {
+            item: self.item.clone(),
+            seq: self.seq,
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<I> fmt::Debug for Head<I>
+where
+    I: Iterator + fmt::Debug,
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Head")
+            .field("item", &self.item)
+            .field("seq", &self.seq)
+            .field("iter", &self.iter)
+            .finish()
+    }
+}
+
+/// A chain of sorted iterators with type I that always yields the smallest next item among all of
+/// them.
+///
+/// Equal items are yielded in the order their iterators appear in the chain. Including an
+/// iterator takes its first item right away, so it can be placed in the merge.
+///
+/// ```
+/// let mut i = chaining_iter::MergeChain::new();
+/// i.include(vec![1, 4, 7].into_iter());
+/// i.include(vec![2, 3, 8].into_iter());
+///
+/// assert_eq!(vec![1, 2, 3, 4, 7, 8], i.collect::<Vec<_>>());
+/// ```
+pub struct MergeChain<I, O = NaturalOrder>
+where
+    I: Iterator,
+{
+    heap: Vec<Head<I>>,
+    order: O,
+    front_seq: i64,
+    back_seq: i64,
+}
+
+impl<I> MergeChain<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    pub fn new() -> MergeChain<I> {
+        MergeChain::with_order(NaturalOrder)
+    }
+}
+
+impl<I> Default for MergeChain<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    fn default() -> Self {
+        MergeChain::new()
+    }
+}
+
+impl<I, F> MergeChain<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    /// Create an empty chain that merges items using the given comparison function.
+    ///
+    /// ```
+    /// let mut i = chaining_iter::MergeChain::merge_by(|a: &i32, b: &i32| b.cmp(a));
+    /// i.include(vec![7, 4, 1].into_iter());
+    /// i.include(vec![8, 3].into_iter());
+    ///
+    /// assert_eq!(vec![8, 7, 4, 3, 1], i.collect::<Vec<_>>());
+    /// ```
+    pub fn merge_by(cmp: F) -> MergeChain<I, F> {
+        MergeChain::with_order(cmp)
+    }
+}
+
+impl<I, F, K> MergeChain<I, KeyOrder<F>>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> K,
+    K: Ord,
+{
+    /// Create an empty chain that merges items by the key returned from the given function.
+    pub fn merge_by_key(key: F) -> MergeChain<I, KeyOrder<F>> {
+        MergeChain::with_order(KeyOrder(key))
+    }
+}
+
+impl<I, O> MergeChain<I, O>
+where
+    I: Iterator,
+    O: MergeOrder<I::Item>,
+{
+    /// Create an empty chain that merges items using the given ordering.
+    pub fn with_order(order: O) -> MergeChain<I, O> {
+        MergeChain {
+            heap: Vec::new(),
+            order,
+            front_seq: -1,
+            back_seq: 0,
+        }
+    }
+
+    /// Include the given iterator in the merge. Its items go after equal items of the iterators
+    /// already in the chain.
+    pub fn include(&mut self, mut new_iter: I) {
+        if let Some(item) = new_iter.next() {
+            let seq = self.back_seq;
+            self.back_seq += 1;
+            self.push(Head {
+                item,
+                seq,
+                iter: new_iter,
+            });
+        }
+    }
+
+    /// Include the given iterator in the merge. Its items go before equal items of the iterators
+    /// already in the chain.
+    pub fn include_front(&mut self, mut new_iter: I) {
+        if let Some(item) = new_iter.next() {
+            let seq = self.front_seq;
+            self.front_seq -= 1;
+            self.push(Head {
+                item,
+                seq,
+                iter: new_iter,
+            });
+        }
+    }
+
+    fn less(order: &mut O, a: &Head<I>, b: &Head<I>) -> bool {
+        order.cmp(&a.item, &b.item).then(a.seq.cmp(&b.seq)) == Ordering::Less
+    }
+
+    fn push(&mut self, head: Head<I>) {
+        self.heap.push(head);
+
+        let mut pos = self.heap.len() - 1;
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !Self::less(&mut self.order, &self.heap[pos], &self.heap[parent]) {
+                break;
+            }
+            self.heap.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let left = 2 * pos + 1;
+            let right = left + 1;
+            let mut smallest = pos;
+            if left < self.heap.len()
+                && Self::less(&mut self.order, &self.heap[left], &self.heap[smallest])
+            {
+                smallest = left;
+            }
+            if right < self.heap.len()
+                && Self::less(&mut self.order, &self.heap[right], &self.heap[smallest])
+            {
+                smallest = right;
+            }
+            if smallest == pos {
+                return;
+            }
+            self.heap.swap(pos, smallest);
+            pos = smallest;
+        }
+    }
+}
+
+impl<I, O> Clone for MergeChain<I, O>
+where
+    I: Iterator + Clone,
+    I::Item: Clone,
+    O: Clone,
+{
+    fn clone(&self) -> Self {
+        MergeChain {
+            heap: self.heap.clone(),
+            order: self.order.clone(),
+            front_seq: self.front_seq,
+            back_seq: self.back_seq,
+        }
+    }
+}
+
+impl<I, O> fmt::Debug for MergeChain<I, O>
+where
+    I: Iterator + fmt::Debug,
+    I::Item: fmt::Debug,
+    O: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MergeChain")
+            .field("heap", &self.heap)
+            .field("order", &self.order)
+            .finish()
+    }
+}
+
+impl<I, O> Iterator for MergeChain<I, O>
+where
+    I: Iterator,
+    O: MergeOrder<I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        // Refill the smallest head in place when possible, instead of a pop followed by a push.
+        let top = &mut self.heap[0];
+        let val = match top.iter.next() {
+            Some(item) => std::mem::replace(&mut top.item, item),
+            None => {
+                let last = self.heap.len() - 1;
+                self.heap.swap(0, last);
+                self.heap.pop()?.item
+            }
+        };
+        self.sift_down(0);
+        Some(val)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        sum_size_hints(self.heap.iter().map(|head| {
+            let (lower, upper) = head.iter.size_hint();
+            (
+                lower.saturating_add(1),
+                upper.and_then(|upper| upper.checked_add(1)),
+            )
+        }))
+    }
+}
+
+impl<I, O> ExactSizeIterator for MergeChain<I, O>
+where
+    I: ExactSizeIterator,
+    O: MergeOrder<I::Item>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::IntoIter;
+
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut i: MergeChain<IntoIter<usize>> = MergeChain::new();
+
+        assert_eq!(None, i.next());
+    }
+
+    #[test]
+    fn merges_sorted() {
+        let mut i = MergeChain::new();
+        i.include(vec![1, 5, 9].into_iter());
+        i.include(vec![].into_iter());
+        i.include(vec![2, 3, 10, 11].into_iter());
+        i.include(vec![0, 6].into_iter());
+
+        assert_eq!(9, i.len());
+        assert_eq!(vec![0, 1, 2, 3, 5, 6, 9, 10, 11], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn ties_in_insertion_order() {
+        let mut i = MergeChain::merge_by_key(|&(key, _): &(u32, char)| key);
+        i.include(vec![(1, 'b'), (2, 'b')].into_iter());
+        i.include(vec![(1, 'c'), (2, 'c')].into_iter());
+        i.include_front(vec![(1, 'a'), (2, 'a')].into_iter());
+
+        let vals: String = i.map(|(_, c)| c).collect();
+        assert_eq!("abcabc", vals);
+    }
+
+    #[test]
+    fn include_mid_merge() {
+        let mut i = MergeChain::new();
+        i.include(vec![1, 4, 7].into_iter());
+
+        assert_eq!(Some(1), i.next());
+        i.include(vec![2, 5].into_iter());
+
+        assert_eq!(vec![2, 4, 5, 7], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn merge_by_reverse() {
+        let mut i = MergeChain::merge_by(|a: &i32, b: &i32| b.cmp(a));
+        i.include(vec![9, 3].into_iter());
+        i.include(vec![8, 4, 1].into_iter());
+
+        assert_eq!(vec![9, 8, 4, 3, 1], i.collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,232 @@
+//! A chain that waits on a channel for more iterators until it is sealed.
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+use crate::{IterChain, SegmentId};
+
+/// A chain of iterators with type I that are sent to it over a channel.
+///
+/// Once the chain runs out, `next` blocks until another iterator is received. It only returns
+/// `None` after every sender has been dropped, or the chain has been sealed.
+///
+/// ```
+/// use std::thread;
+///
+/// let (sender, chain) = chaining_iter::OpenChain::new();
+/// let handle = thread::spawn(move || {
+///     sender.send(0..2).unwrap();
+///     sender.send(2..4).unwrap();
+/// });
+///
+/// assert_eq!(vec![0, 1, 2, 3], chain.collect::<Vec<_>>());
+/// # handle.join().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct OpenChain<I> {
+    chain: IterChain<I>,
+    /// Dropped once the chain is sealed, so that senders find out.
+    receiver: Option<Receiver<I>>,
+}
+
+impl<I> OpenChain<I>
+where
+    I: Iterator,
+{
+    /// Create an empty chain, along with the sender used to include iterators in it.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> (Sender<I>, OpenChain<I>) {
+        let (sender, receiver) = mpsc::channel();
+        (sender, OpenChain::from_receiver(receiver))
+    }
+
+    /// Create an empty chain that includes the iterators received from the given channel.
+    pub fn from_receiver(receiver: Receiver<I>) -> OpenChain<I> {
+        OpenChain {
+            chain: IterChain::new(),
+            receiver: Some(receiver),
+        }
+    }
+
+    /// Include the given iterator at the end of the chain, before any that have not been
+    /// received yet, returning its id.
+    pub fn include(&mut self, new_iter: I) -> SegmentId {
+        self.chain.include(new_iter)
+    }
+
+    /// Include the given iterator at the front of the chain, returning its id.
+    pub fn include_front(&mut self, new_iter: I) -> SegmentId {
+        self.chain.include_front(new_iter)
+    }
+
+    /// Stop waiting for more iterators. Iterators already sent are still included, and the
+    /// receiver is dropped, so sending any more fails with a `SendError`.
+    ///
+    /// A send that races with sealing may still return `Ok`, and its iterator is then dropped
+    /// unread. Every send that starts after `seal` returns fails.
+    pub fn seal(&mut self) {
+        if let Some(receiver) = self.receiver.take() {
+            for iter in receiver.try_iter() {
+                self.chain.include(iter);
+            }
+        }
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        self.receiver.is_none()
+    }
+
+    /// Take the next item, waiting at most `timeout` for another iterator to be received.
+    ///
+    /// Returns `RecvTimeoutError::Disconnected` once every sender has been dropped or the chain
+    /// has been sealed, and the chain is empty.
+    pub fn next_timeout(&mut self, timeout: Duration) -> Result<I::Item, RecvTimeoutError> {
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            if let Some(val) = self.chain.next() {
+                return Ok(val);
+            }
+            let receiver = match &self.receiver {
+                Some(receiver) => receiver,
+                None => return Err(RecvTimeoutError::Disconnected),
+            };
+
+            let received = match deadline {
+                Some(deadline) => {
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                // The deadline is too far away to represent, so wait without one.
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(iter) => {
+                    self.chain.include(iter);
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.receiver = None;
+                    return Err(RecvTimeoutError::Disconnected);
+                }
+                Err(RecvTimeoutError::Timeout) => return Err(RecvTimeoutError::Timeout),
+            }
+        }
+    }
+}
+
+impl<I> Iterator for OpenChain<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    /// Take the next item, blocking until another iterator is received if the chain is empty.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let val = self.chain.next();
+            if val.is_some() {
+                return val;
+            }
+
+            match self.receiver.as_ref()?.recv() {
+                Ok(iter) => {
+                    self.chain.include(iter);
+                }
+                Err(_) => self.receiver = None,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.chain.size_hint();
+        if self.is_sealed() {
+            (lower, upper)
+        } else {
+            (lower, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn ends_when_senders_dropped() {
+        let (sender, mut chain) = OpenChain::new();
+        sender.send(0..2).unwrap();
+        let other = sender.clone();
+        drop(sender);
+        other.send(2..3).unwrap();
+        drop(other);
+
+        assert_eq!(vec![0, 1, 2], chain.by_ref().collect::<Vec<_>>());
+        assert!(chain.is_sealed());
+        assert_eq!(None, chain.next());
+    }
+
+    #[test]
+    fn seal_keeps_already_sent() {
+        let (sender, mut chain) = OpenChain::new();
+        sender.send(0..2).unwrap();
+        chain.include_front(10..11);
+        chain.seal();
+        assert!(sender.send(5..6).is_err());
+
+        assert_eq!(vec![10, 0, 1], chain.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn seal_while_sending() {
+        let (sender, mut chain) = OpenChain::new();
+        let handle = thread::spawn(move || {
+            let mut sent = 0;
+            while sender.send(sent..sent + 1).is_ok() {
+                sent += 1;
+            }
+            sent
+        });
+
+        assert_eq!(Some(0), chain.next());
+        chain.seal();
+        let received: Vec<_> = chain.collect();
+        let sent = handle.join().unwrap();
+
+        // Sends racing with `seal` may succeed without being received, but nothing is reordered.
+        assert!(received.len() < sent);
+        assert_eq!((1..received.len() + 1).collect::<Vec<_>>(), received);
+    }
+
+    #[test]
+    fn next_timeout() {
+        let (sender, mut chain) = OpenChain::<Range<usize>>::new();
+        sender.send(0..1).unwrap();
+
+        assert_eq!(Ok(0), chain.next_timeout(Duration::from_millis(10)));
+        assert_eq!(
+            Err(RecvTimeoutError::Timeout),
+            chain.next_timeout(Duration::from_millis(10))
+        );
+
+        drop(sender);
+        assert_eq!(
+            Err(RecvTimeoutError::Disconnected),
+            chain.next_timeout(Duration::from_millis(10))
+        );
+    }
+
+    #[test]
+    fn waits_for_sender_thread() {
+        let (sender, chain) = OpenChain::new();
+        let handle = thread::spawn(move || {
+            for n in 0..3 {
+                sender.send(n * 2..n * 2 + 2).unwrap();
+                thread::yield_now();
+            }
+        });
+
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], chain.collect::<Vec<_>>());
+        handle.join().unwrap();
+    }
+}
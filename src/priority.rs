@@ -0,0 +1,146 @@
+//! Strict priority scheduling between a chain of iterators.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::sum_size_hints;
+
+/// A chain of iterators with type I, each included with a priority P.
+///
+/// Items are always taken from the highest priority iterator left in the chain. Iterators with
+/// the same priority are drained in order, like in an `IterChain`. Including an iterator with a
+/// higher priority than the current one preempts it on the next call to `next`.
+///
+/// ```
+/// let mut i = chaining_iter::PriorityChain::new();
+/// i.include_with_priority(0..3, 1);
+///
+/// assert_eq!(Some(0), i.next());
+/// i.include_with_priority(10..12, 5);
+///
+/// assert_eq!(vec![10, 11, 1, 2], i.collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PriorityChain<I, P> {
+    queues: BTreeMap<P, VecDeque<I>>,
+}
+
+impl<I, P> PriorityChain<I, P>
+where
+    I: Iterator,
+    P: Ord,
+{
+    pub fn new() -> PriorityChain<I, P> {
+        PriorityChain {
+            queues: BTreeMap::new(),
+        }
+    }
+
+    /// Include the given iterator after all of the iterators with the same priority.
+    pub fn include_with_priority(&mut self, new_iter: I, priority: P) {
+        self.queues.entry(priority).or_default().push_back(new_iter);
+    }
+
+    /// Include the given iterator before all of the iterators with the same priority.
+    pub fn include_front_with_priority(&mut self, new_iter: I, priority: P) {
+        self.queues
+            .entry(priority)
+            .or_default()
+            .push_front(new_iter);
+    }
+}
+
+impl<I, P> Default for PriorityChain<I, P>
+where
+    I: Iterator,
+    P: Ord,
+{
+    fn default() -> Self {
+        PriorityChain::new()
+    }
+}
+
+impl<I, P> Iterator for PriorityChain<I, P>
+where
+    I: Iterator,
+    P: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut entry = self.queues.last_entry()?;
+            let queue = entry.get_mut();
+            if let Some(iter) = queue.front_mut() {
+                let val = iter.next();
+                if val.is_some() {
+                    return val;
+                }
+                queue.pop_front();
+            }
+            if queue.is_empty() {
+                entry.remove();
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        sum_size_hints(self.queues.values().flatten().map(Iterator::size_hint))
+    }
+}
+
+impl<I, P> ExactSizeIterator for PriorityChain<I, P>
+where
+    I: ExactSizeIterator,
+    P: Ord,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut i: PriorityChain<Range<usize>, u8> = PriorityChain::new();
+
+        assert_eq!(None, i.next());
+    }
+
+    #[test]
+    fn highest_priority_first() {
+        let mut i = PriorityChain::new();
+        i.include_with_priority(0..2, 0);
+        i.include_with_priority(10..12, 2);
+        i.include_with_priority(20..20, 3);
+        i.include_with_priority(30..32, 1);
+
+        assert_eq!(6, i.len());
+        assert_eq!(vec![10, 11, 30, 31, 0, 1], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn equal_priority_in_order() {
+        let mut i = PriorityChain::new();
+        i.include_with_priority(0..2, 1);
+        i.include_with_priority(10..12, 1);
+        i.include_front_with_priority(20..21, 1);
+
+        assert_eq!(vec![20, 0, 1, 10, 11], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn preempts_current() {
+        let mut i = PriorityChain::new();
+        i.include_with_priority(0..3, 1);
+        i.include_with_priority(10..12, 1);
+
+        assert_eq!(Some(0), i.next());
+        i.include_with_priority(20..22, 2);
+        assert_eq!(Some(20), i.next());
+        i.include_with_priority(30..31, 0);
+
+        assert_eq!(vec![21, 1, 2, 10, 11, 30], i.collect::<Vec<_>>());
+    }
+}
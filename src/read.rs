@@ -0,0 +1,230 @@
+//! A chain of readers.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, IoSliceMut, Read};
+
+/// A chain of readers with type R, read one after the other like an `IterChain`.
+///
+/// Unlike `Read::chain`, any number of readers can be included at run time, and the type doesn't
+/// grow with each one.
+///
+/// ```
+/// use std::io::Read;
+///
+/// let mut r = chaining_iter::ReadChain::new();
+/// r.include(&b"world"[..]);
+/// r.include_front(&b"hello "[..]);
+///
+/// let mut s = String::new();
+/// r.read_to_string(&mut s).unwrap();
+/// assert_eq!("hello world", s);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadChain<R> {
+    readers: VecDeque<R>,
+}
+
+impl<R> ReadChain<R>
+where
+    R: Read,
+{
+    pub fn new() -> ReadChain<R> {
+        ReadChain {
+            readers: VecDeque::new(),
+        }
+    }
+
+    /// Include the given reader at the end of the chain.
+    pub fn include(&mut self, new_reader: R) {
+        self.readers.push_back(new_reader);
+    }
+
+    /// Include the given reader at the front of the chain.
+    pub fn include_front(&mut self, new_reader: R) {
+        self.readers.push_front(new_reader);
+    }
+
+    /// Take all of the readers that have not reached their end yet out of the chain.
+    pub fn into_inner(self) -> VecDeque<R> {
+        self.readers
+    }
+}
+
+impl<R> Default for ReadChain<R>
+where
+    R: Read,
+{
+    fn default() -> Self {
+        ReadChain::new()
+    }
+}
+
+impl<R> Read for ReadChain<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Reading into an empty buffer returns 0 without reaching the end of the reader.
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while let Some(reader) = self.readers.front_mut() {
+            let n = reader.read(buf)?;
+            if n > 0 {
+                return Ok(n);
+            }
+            self.readers.pop_front();
+        }
+        Ok(0)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        if bufs.iter().all(|buf| buf.is_empty()) {
+            return Ok(0);
+        }
+        while let Some(reader) = self.readers.front_mut() {
+            let n = reader.read_vectored(bufs)?;
+            if n > 0 {
+                return Ok(n);
+            }
+            self.readers.pop_front();
+        }
+        Ok(0)
+    }
+
+    /// Hands each reader to its own `read_to_end`, so readers that know their size can reserve
+    /// space for it up front.
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let mut total = 0;
+        while let Some(reader) = self.readers.front_mut() {
+            total += reader.read_to_end(buf)?;
+            self.readers.pop_front();
+        }
+        Ok(total)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        // A character can be split between two readers, so the bytes are only checked once they
+        // have all been read.
+        let mut bytes = Vec::new();
+        let n = self.read_to_end(&mut bytes)?;
+        let s = String::from_utf8(bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.utf8_error()))?;
+        buf.push_str(&s);
+        Ok(n)
+    }
+}
+
+impl<R> BufRead for ReadChain<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while let Some(reader) = self.readers.front_mut() {
+            if !reader.fill_buf()?.is_empty() {
+                break;
+            }
+            self.readers.pop_front();
+        }
+        // Filling the buffer again only returns what is already buffered.
+        match self.readers.front_mut() {
+            Some(reader) => reader.fill_buf(),
+            None => Ok(&[]),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Some(reader) = self.readers.front_mut() {
+            reader.consume(amt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut r: ReadChain<&[u8]> = ReadChain::new();
+        let mut buf = [0; 4];
+
+        assert_eq!(0, r.read(&mut buf).unwrap());
+    }
+
+    #[test]
+    fn reads_across_readers() {
+        let mut r = ReadChain::new();
+        r.include(&b"ab"[..]);
+        r.include(&b""[..]);
+        r.include(&b"cde"[..]);
+
+        let mut buf = [0; 4];
+        assert_eq!(2, r.read(&mut buf).unwrap());
+        assert_eq!(b"ab", &buf[..2]);
+        assert_eq!(0, r.read(&mut []).unwrap());
+        assert_eq!(3, r.read(&mut buf).unwrap());
+        assert_eq!(b"cde", &buf[..3]);
+        assert_eq!(0, r.read(&mut buf).unwrap());
+    }
+
+    #[test]
+    fn read_vectored() {
+        let mut r = ReadChain::new();
+        r.include(&b""[..]);
+        r.include(&b"abcd"[..]);
+
+        let mut a = [0; 1];
+        let mut b = [0; 2];
+        let n = r
+            .read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+            .unwrap();
+        assert_eq!(3, n);
+        assert_eq!((b"a", b"bc"), (&a, &b));
+    }
+
+    #[test]
+    fn read_to_end_and_string() {
+        let mut r = ReadChain::new();
+        r.include(Cursor::new(vec![0xc3]));
+        r.include(Cursor::new(vec![0xa9, b'!']));
+
+        let mut s = String::new();
+        assert_eq!(3, r.read_to_string(&mut s).unwrap());
+        assert_eq!("é!", s);
+
+        r.include(Cursor::new(vec![0xff]));
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            r.read_to_string(&mut s).unwrap_err().kind()
+        );
+        assert_eq!("é!", s);
+    }
+
+    #[test]
+    fn buf_read_lines() {
+        let mut r = ReadChain::new();
+        r.include(&b"one\ntw"[..]);
+        r.include(&b""[..]);
+        r.include(&b"o\nthree"[..]);
+
+        let lines: Vec<_> = r.lines().map(Result::unwrap).collect();
+        assert_eq!(vec!["one", "two", "three"], lines);
+    }
+
+    #[test]
+    fn include_mid_read() {
+        let mut r = ReadChain::new();
+        r.include(&b"abc"[..]);
+
+        let mut buf = [0; 2];
+        r.read_exact(&mut buf).unwrap();
+        r.include_front(&b"xy"[..]);
+
+        let mut rest = Vec::new();
+        r.read_to_end(&mut rest).unwrap();
+        assert_eq!(b"xyc", &rest[..]);
+    }
+}
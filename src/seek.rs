@@ -0,0 +1,296 @@
+//! A seekable chain of readers with known lengths.
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// A chain of readers with type R that acts like a single file made of their contents, one after
+/// the other.
+///
+/// Each reader's length is found with `SeekFrom::End` when it is included, or given by the caller.
+/// The start of each reader in the chain is kept in a table, so seeking finds the right reader
+/// with a binary search. Readers are kept after they are read, so the chain can seek back to them.
+///
+/// ```
+/// use std::io::{Cursor, Read, Seek, SeekFrom};
+///
+/// let mut r = chaining_iter::SeekChain::new();
+/// r.include(Cursor::new(b"hello ".to_vec())).unwrap();
+/// r.include(Cursor::new(b"world".to_vec())).unwrap();
+///
+/// let mut s = String::new();
+/// r.seek(SeekFrom::Start(4)).unwrap();
+/// r.read_to_string(&mut s).unwrap();
+/// assert_eq!("o world", s);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeekChain<R> {
+    readers: Vec<R>,
+    /// The position in the chain where each reader starts.
+    starts: Vec<u64>,
+    len: u64,
+    pos: u64,
+    /// The reader whose own position is known to match `pos`, so it doesn't need a seek.
+    synced: Option<usize>,
+}
+
+impl<R> SeekChain<R> {
+    pub fn new() -> SeekChain<R> {
+        SeekChain {
+            readers: Vec::new(),
+            starts: Vec::new(),
+            len: 0,
+            pos: 0,
+            synced: None,
+        }
+    }
+
+    /// The combined length of all of the readers.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Include the given reader at the end of the chain, with the given length. The chain will
+    /// never read past that length from it.
+    ///
+    /// Fails with `InvalidInput` if the combined length would overflow a `u64`.
+    pub fn include_with_len(&mut self, new_reader: R, len: u64) -> io::Result<()> {
+        let new_len = self.len.checked_add(len).ok_or_else(too_long)?;
+        self.readers.push(new_reader);
+        self.starts.push(self.len);
+        self.len = new_len;
+        Ok(())
+    }
+
+    /// Include the given reader at the front of the chain, with the given length. The position of
+    /// the chain moves along with the data it was pointing at.
+    ///
+    /// Fails with `InvalidInput` if the combined length or the moved position would overflow a
+    /// `u64`.
+    pub fn include_front_with_len(&mut self, new_reader: R, len: u64) -> io::Result<()> {
+        let new_len = self.len.checked_add(len).ok_or_else(too_long)?;
+        let new_pos = self.pos.checked_add(len).ok_or_else(too_long)?;
+        self.readers.insert(0, new_reader);
+        // Every start is at most the old length, so these can't overflow either.
+        self.starts.iter_mut().for_each(|start| *start += len);
+        self.starts.insert(0, 0);
+        self.len = new_len;
+        self.pos = new_pos;
+        self.synced = self.synced.map(|index| index + 1);
+        Ok(())
+    }
+
+    /// The index of the reader that holds the byte at `pos`.
+    fn find(&self, pos: u64) -> Option<usize> {
+        // Empty readers share their start with the next one, so take the last of them.
+        self.starts
+            .partition_point(|&start| start <= pos)
+            .checked_sub(1)
+    }
+
+    fn reader_len(&self, index: usize) -> u64 {
+        self.starts.get(index + 1).copied().unwrap_or(self.len) - self.starts[index]
+    }
+}
+
+impl<R> SeekChain<R>
+where
+    R: Seek,
+{
+    /// Include the given reader at the end of the chain, finding its length by seeking to its
+    /// end.
+    pub fn include(&mut self, mut new_reader: R) -> io::Result<()> {
+        let len = new_reader.seek(SeekFrom::End(0))?;
+        self.include_with_len(new_reader, len)
+    }
+
+    /// Include the given reader at the front of the chain, finding its length by seeking to its
+    /// end. The position of the chain moves along with the data it was pointing at.
+    pub fn include_front(&mut self, mut new_reader: R) -> io::Result<()> {
+        let len = new_reader.seek(SeekFrom::End(0))?;
+        self.include_front_with_len(new_reader, len)
+    }
+}
+
+impl<R> Default for SeekChain<R> {
+    fn default() -> Self {
+        SeekChain::new()
+    }
+}
+
+impl<R> Read for SeekChain<R>
+where
+    R: Read + Seek,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len {
+            return Ok(0);
+        }
+        let index = match self.find(self.pos) {
+            Some(index) => index,
+            None => return Ok(0),
+        };
+
+        let offset = self.pos - self.starts[index];
+        let left = self.reader_len(index) - offset;
+        if self.synced != Some(index) {
+            self.readers[index].seek(SeekFrom::Start(offset))?;
+            self.synced = Some(index);
+        }
+
+        let max = buf.len().min(usize::try_from(left).unwrap_or(usize::MAX));
+        let n = match self.readers[index].read(&mut buf[..max]) {
+            Ok(n) => n,
+            Err(err) => {
+                self.synced = None;
+                return Err(err);
+            }
+        };
+        if n == 0 {
+            self.synced = None;
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "reader ended before its length",
+            ));
+        }
+
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R> Seek for SeekChain<R>
+where
+    R: Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(delta) => offset(self.len, delta),
+            SeekFrom::Current(delta) => offset(self.pos, delta),
+        };
+        match new_pos {
+            Some(new_pos) => {
+                if new_pos != self.pos {
+                    self.pos = new_pos;
+                    self.synced = None;
+                }
+                Ok(new_pos)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+fn too_long() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "combined length of the readers overflows",
+    )
+}
+
+fn offset(base: u64, delta: i64) -> Option<u64> {
+    if delta >= 0 {
+        base.checked_add(delta as u64)
+    } else {
+        base.checked_sub(delta.unsigned_abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn chain(parts: &[&[u8]]) -> SeekChain<Cursor<Vec<u8>>> {
+        let mut r = SeekChain::new();
+        for part in parts {
+            r.include(Cursor::new(part.to_vec())).unwrap();
+        }
+        r
+    }
+
+    fn read_all(r: &mut SeekChain<Cursor<Vec<u8>>>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn reads_in_order() {
+        let mut r = chain(&[b"ab", b"", b"cde"]);
+
+        assert_eq!(5, r.len());
+        assert_eq!(b"abcde".to_vec(), read_all(&mut r));
+    }
+
+    #[test]
+    fn seek_across_readers() {
+        let mut r = chain(&[b"ab", b"", b"cde", b"f"]);
+
+        assert_eq!(3, r.seek(SeekFrom::Start(3)).unwrap());
+        assert_eq!(b"def".to_vec(), read_all(&mut r));
+        assert_eq!(1, r.seek(SeekFrom::End(-5)).unwrap());
+        let mut buf = [0; 2];
+        r.read_exact(&mut buf).unwrap();
+        assert_eq!(b"bc", &buf);
+        assert_eq!(2, r.seek(SeekFrom::Current(-1)).unwrap());
+        assert_eq!(b"cdef".to_vec(), read_all(&mut r));
+
+        assert!(r.seek(SeekFrom::Current(-7)).is_err());
+        assert_eq!(10, r.seek(SeekFrom::Start(10)).unwrap());
+        assert_eq!(Vec::<u8>::new(), read_all(&mut r));
+    }
+
+    #[test]
+    fn include_later() {
+        let mut r = chain(&[b"ab"]);
+        assert_eq!(b"ab".to_vec(), read_all(&mut r));
+
+        r.include(Cursor::new(b"cd".to_vec())).unwrap();
+        assert_eq!(b"cd".to_vec(), read_all(&mut r));
+
+        r.include_front(Cursor::new(b"xy".to_vec())).unwrap();
+        assert_eq!(6, r.stream_position().unwrap());
+        r.seek(SeekFrom::Start(1)).unwrap();
+        assert_eq!(b"yabcd".to_vec(), read_all(&mut r));
+    }
+
+    #[test]
+    fn given_len_limits_reader() {
+        let mut r = SeekChain::new();
+        r.include_with_len(Cursor::new(b"abcdef".to_vec()), 2)
+            .unwrap();
+        r.include_with_len(Cursor::new(b"gh".to_vec()), 2).unwrap();
+
+        assert_eq!(b"abgh".to_vec(), read_all(&mut r));
+
+        r.include_with_len(Cursor::new(b"i".to_vec()), 3).unwrap();
+        let mut buf = Vec::new();
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            r.read_to_end(&mut buf).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn len_overflow() {
+        let half = u64::MAX / 2 + 1;
+        let mut r = SeekChain::new();
+        r.include_with_len(io::empty(), half).unwrap();
+
+        let err = r.include_with_len(io::empty(), half).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        let err = r.include_front_with_len(io::empty(), half).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!(half, r.len());
+        assert_eq!(Some(0), r.find(half - 1));
+    }
+}
@@ -0,0 +1,211 @@
+//! Concurrent merging of a chain of async streams, enabled by the `futures` feature.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+use futures::stream::{FusedStream, Stream, StreamExt};
+
+use crate::sum_size_hints;
+
+/// A set of streams with type S, yielding items from whichever stream is ready first.
+///
+/// By default the streams take turns being polled first, so a busy stream can't starve the rest.
+/// A [`biased`](SelectChain::biased) chain always polls from the front instead. Exhausted streams
+/// are dropped, and including a stream wakes the task that last saw the chain pending.
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::stream::{self, StreamExt};
+///
+/// let mut s = chaining_iter::SelectChain::new();
+/// s.include(stream::iter(vec![0, 1]));
+/// s.include(stream::iter(vec![10, 11]));
+///
+/// assert_eq!(vec![0, 10, 1, 11], block_on(s.collect::<Vec<_>>()));
+/// ```
+#[derive(Debug)]
+pub struct SelectChain<S> {
+    streams: VecDeque<S>,
+    /// The index of the stream to poll first.
+    start: usize,
+    fair: bool,
+    /// The waker of the last poll that returned `Poll::Pending`.
+    waker: Option<Waker>,
+}
+
+impl<S> SelectChain<S>
+where
+    S: Stream + Unpin,
+{
+    /// Create an empty set that takes turns polling its streams first.
+    pub fn new() -> SelectChain<S> {
+        SelectChain {
+            streams: VecDeque::new(),
+            start: 0,
+            fair: true,
+            waker: None,
+        }
+    }
+
+    /// Create an empty set that always polls its streams from front to back.
+    pub fn biased() -> SelectChain<S> {
+        SelectChain {
+            fair: false,
+            ..SelectChain::new()
+        }
+    }
+
+    /// Include the given stream at the end of the chain.
+    pub fn include(&mut self, new_stream: S) {
+        self.streams.push_back(new_stream);
+        self.wake();
+    }
+
+    /// Include the given stream at the front of the chain.
+    pub fn include_front(&mut self, new_stream: S) {
+        self.streams.push_front(new_stream);
+        self.start += 1;
+        self.wake();
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<S> Default for SelectChain<S>
+where
+    S: Stream + Unpin,
+{
+    fn default() -> Self {
+        SelectChain::new()
+    }
+}
+
+impl<S> Stream for SelectChain<S>
+where
+    S: Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut index = if self.fair { self.start } else { 0 };
+        for _ in 0..self.streams.len() {
+            if index >= self.streams.len() {
+                index = 0;
+            }
+            match self.streams[index].poll_next_unpin(cx) {
+                Poll::Ready(Some(val)) => {
+                    self.start = index + 1;
+                    return Poll::Ready(Some(val));
+                }
+                Poll::Ready(None) => {
+                    // The next stream moves into this index.
+                    self.streams.remove(index);
+                }
+                Poll::Pending => index += 1,
+            }
+        }
+
+        if self.streams.is_empty() {
+            Poll::Ready(None)
+        } else {
+            self.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        sum_size_hints(self.streams.iter().map(Stream::size_hint))
+    }
+}
+
+// `is_terminated` only says that polling now won't yield an item. Including another stream makes
+// the chain live again, which `FusedStream` allows.
+impl<S> FusedStream for SelectChain<S>
+where
+    S: Stream + Unpin,
+{
+    fn is_terminated(&self) -> bool {
+        self.streams.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+    use futures::executor::block_on;
+    use futures::stream;
+    use futures::task::waker;
+
+    use super::*;
+    use crate::test_util::CountWakes;
+
+    #[test]
+    fn yields_from_ready_stream() {
+        let counter = Arc::new(CountWakes::default());
+        let waker = waker(Arc::clone(&counter));
+        let mut cx = Context::from_waker(&waker);
+
+        let (a_tx, a_rx) = unbounded();
+        let (b_tx, b_rx) = unbounded();
+        let mut s: SelectChain<UnboundedReceiver<usize>> = SelectChain::new();
+        s.include(a_rx);
+        s.include(b_rx);
+
+        assert_eq!(Poll::Pending, s.poll_next_unpin(&mut cx));
+        b_tx.unbounded_send(10).unwrap();
+        assert_eq!(1, counter.count());
+        assert_eq!(Poll::Ready(Some(10)), s.poll_next_unpin(&mut cx));
+
+        a_tx.unbounded_send(0).unwrap();
+        assert_eq!(Poll::Ready(Some(0)), s.poll_next_unpin(&mut cx));
+
+        drop(a_tx);
+        drop(b_tx);
+        assert_eq!(Poll::Ready(None), s.poll_next_unpin(&mut cx));
+        assert!(s.is_terminated());
+    }
+
+    #[test]
+    fn include_wakes_pending() {
+        let counter = Arc::new(CountWakes::default());
+        let waker = waker(Arc::clone(&counter));
+        let mut cx = Context::from_waker(&waker);
+
+        let (_tx, rx) = unbounded();
+        let mut s = SelectChain::new();
+        s.include(rx);
+        assert_eq!(Poll::Pending, s.poll_next_unpin(&mut cx));
+
+        let (tx, other) = unbounded();
+        tx.unbounded_send(5).unwrap();
+        s.include(other);
+        assert_eq!(1, counter.count());
+        assert_eq!(Poll::Ready(Some(5)), s.poll_next_unpin(&mut cx));
+    }
+
+    #[test]
+    fn fair_takes_turns() {
+        let mut s = SelectChain::new();
+        s.include(stream::iter(vec![0, 1, 2]));
+        s.include(stream::iter(vec![10]));
+        s.include(stream::iter(vec![20, 21]));
+
+        assert_eq!(vec![0, 10, 20, 1, 21, 2], block_on(s.collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn biased_polls_front_first() {
+        let mut s = SelectChain::biased();
+        s.include(stream::iter(vec![0, 1]));
+        s.include_front(stream::iter(vec![10]));
+
+        assert_eq!(vec![10, 0, 1], block_on(s.collect::<Vec<_>>()));
+    }
+}
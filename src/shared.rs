@@ -0,0 +1,408 @@
+//! A chain that producer threads can include iterators in while a consumer iterates it.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::{sum_size_hints, IterChain};
+
+/// Why `SharedIterChain::try_next` did not return an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TryNextError {
+    /// The chain is empty for now, but producers may still include more iterators.
+    Empty,
+    /// The chain is empty and every producer has been dropped.
+    Closed,
+}
+
+impl fmt::Display for TryNextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryNextError::Empty => write!(f, "chain is empty"),
+            TryNextError::Closed => write!(f, "chain is empty and closed"),
+        }
+    }
+}
+
+impl Error for TryNextError {}
+
+#[derive(Debug)]
+struct State<I> {
+    chain: IterChain<I>,
+    producers: usize,
+    /// Cleared once the consumer is dropped, so producers get their iterators back.
+    consumer: bool,
+}
+
+#[derive(Debug)]
+struct Shared<I> {
+    state: Mutex<State<I>>,
+    /// Signaled whenever an iterator is included or the last producer is dropped.
+    changed: Condvar,
+    /// The number of iterators included at the front since the consumer took its current
+    /// iterator out of the chain. Only changed while holding the lock.
+    front_included: AtomicUsize,
+}
+
+impl<I> Shared<I> {
+    /// A panic while the lock was held can't leave the chain half updated, so poisoning is
+    /// ignored.
+    fn lock(&self) -> MutexGuard<'_, State<I>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn include(&self, new_iter: I, front: bool) -> Result<(), I>
+    where
+        I: Iterator,
+    {
+        {
+            let mut state = self.lock();
+            if !state.consumer {
+                return Err(new_iter);
+            }
+            if front {
+                state.chain.include_front(new_iter);
+                self.front_included.fetch_add(1, Ordering::Release);
+            } else {
+                state.chain.include(new_iter);
+            }
+        }
+        self.changed.notify_one();
+        Ok(())
+    }
+}
+
+/// The consuming end of a chain of iterators with type I that is shared between threads.
+///
+/// Iterators are included through [`ChainProducer`] handles. `next` blocks until an item is
+/// available, and returns `None` once the chain is empty and every producer has been dropped.
+///
+/// The consumer takes the iterator at the front of the chain out of the lock before polling it,
+/// so a slow iterator doesn't block producers. Iterators included at the front still go before
+/// it, and are picked up before its next item is taken.
+///
+/// ```
+/// use std::thread;
+///
+/// let (producer, consumer) = chaining_iter::SharedIterChain::new();
+///
+/// let handles: Vec<_> = (0..2)
+///     .map(|n| {
+///         let producer = producer.clone();
+///         thread::spawn(move || {
+///             producer.include(n * 10..n * 10 + 3).unwrap();
+///         })
+///     })
+///     .collect();
+/// drop(producer);
+///
+/// let mut items: Vec<_> = consumer.collect();
+/// items.sort();
+/// assert_eq!(vec![0, 1, 2, 10, 11, 12], items);
+/// # for handle in handles {
+/// #     handle.join().unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SharedIterChain<I> {
+    shared: Arc<Shared<I>>,
+    /// The iterator taken out of the chain, polled without holding the lock.
+    current: Option<I>,
+}
+
+/// A handle that includes iterators in a [`SharedIterChain`]. The chain is closed once every
+/// handle is dropped.
+///
+/// Once the consumer has been dropped, including an iterator hands it back instead.
+#[derive(Debug)]
+pub struct ChainProducer<I> {
+    shared: Arc<Shared<I>>,
+}
+
+impl<I> SharedIterChain<I>
+where
+    I: Iterator,
+{
+    /// Create an empty chain, along with its first producer.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> (ChainProducer<I>, SharedIterChain<I>) {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                chain: IterChain::new(),
+                producers: 1,
+                consumer: true,
+            }),
+            changed: Condvar::new(),
+            front_included: AtomicUsize::new(0),
+        });
+        (
+            ChainProducer {
+                shared: Arc::clone(&shared),
+            },
+            SharedIterChain {
+                shared,
+                current: None,
+            },
+        )
+    }
+
+    /// Include the given iterator at the end of the chain.
+    pub fn include(&self, new_iter: I) {
+        // The consumer is still alive, so this can't fail.
+        let _ = self.shared.include(new_iter, false);
+    }
+
+    /// Include the given iterator at the front of the chain.
+    pub fn include_front(&self, new_iter: I) {
+        let _ = self.shared.include(new_iter, true);
+    }
+
+    /// Take the next item without blocking.
+    pub fn try_next(&mut self) -> Result<I::Item, TryNextError> {
+        loop {
+            if let Some(val) = self.next_current() {
+                return Ok(val);
+            }
+            let mut state = self.shared.lock();
+            match state.chain.remove_at(0) {
+                Some(iter) => {
+                    self.shared.front_included.store(0, Ordering::Release);
+                    self.current = Some(iter);
+                }
+                None if state.producers == 0 => return Err(TryNextError::Closed),
+                None => return Err(TryNextError::Empty),
+            }
+        }
+    }
+
+    fn next_current(&mut self) -> Option<I::Item> {
+        if self.shared.front_included.load(Ordering::Acquire) > 0 {
+            self.requeue_current();
+        }
+        let val = self.current.as_mut()?.next();
+        if val.is_none() {
+            self.current = None;
+        }
+        val
+    }
+
+    /// Put the current iterator back in the chain, after the iterators included at the front
+    /// since it was taken out.
+    fn requeue_current(&mut self) {
+        let mut state = self.shared.lock();
+        let index = self.shared.front_included.swap(0, Ordering::AcqRel);
+        if let Some(iter) = self.current.take() {
+            state.chain.include_at(index, iter);
+        }
+    }
+}
+
+impl<I> Drop for SharedIterChain<I> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.consumer = false;
+        // Nothing will take items from the pending iterators any more.
+        state.chain.front = None;
+        state.chain.iters.clear();
+        state.chain.back = None;
+    }
+}
+
+impl<I> Iterator for SharedIterChain<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    /// Take the next item, blocking until one is available or every producer has been dropped.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(val) = self.next_current() {
+                return Some(val);
+            }
+            let mut state = self.shared.lock();
+            loop {
+                if let Some(iter) = state.chain.remove_at(0) {
+                    self.shared.front_included.store(0, Ordering::Release);
+                    self.current = Some(iter);
+                    break;
+                }
+                if state.producers == 0 {
+                    return None;
+                }
+                state = self
+                    .shared
+                    .changed
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let state = self.shared.lock();
+        let (lower, upper) = sum_size_hints(
+            self.current
+                .iter()
+                .map(Iterator::size_hint)
+                .chain(Some(state.chain.size_hint())),
+        );
+        if state.producers == 0 {
+            (lower, upper)
+        } else {
+            (lower, None)
+        }
+    }
+}
+
+impl<I> ChainProducer<I>
+where
+    I: Iterator,
+{
+    /// Include the given iterator at the end of the chain. If the consumer has been dropped, the
+    /// iterator is handed back.
+    pub fn include(&self, new_iter: I) -> Result<(), I> {
+        self.shared.include(new_iter, false)
+    }
+
+    /// Include the given iterator at the front of the chain. If the consumer has been dropped,
+    /// the iterator is handed back.
+    pub fn include_front(&self, new_iter: I) -> Result<(), I> {
+        self.shared.include(new_iter, true)
+    }
+}
+
+impl<I> Clone for ChainProducer<I> {
+    fn clone(&self) -> Self {
+        self.shared.lock().producers += 1;
+        ChainProducer {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<I> Drop for ChainProducer<I> {
+    fn drop(&mut self) {
+        let closed = {
+            let mut state = self.shared.lock();
+            state.producers -= 1;
+            state.producers == 0
+        };
+        if closed {
+            self.shared.changed.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn closed_when_producers_dropped() {
+        let (producer, mut consumer) = SharedIterChain::<Range<usize>>::new();
+        let other = producer.clone();
+        drop(producer);
+
+        assert_eq!(Err(TryNextError::Empty), consumer.try_next());
+        other.include(0..1).unwrap();
+        drop(other);
+
+        assert_eq!(Ok(0), consumer.try_next());
+        assert_eq!(Err(TryNextError::Closed), consumer.try_next());
+        assert_eq!(None, consumer.next());
+    }
+
+    #[test]
+    fn include_front_from_consumer() {
+        let (producer, mut consumer) = SharedIterChain::new();
+        producer.include(0..2).unwrap();
+        consumer.include_front(10..11);
+        drop(producer);
+
+        assert_eq!(vec![10, 0, 1], consumer.by_ref().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn include_front_before_current() {
+        let (producer, mut consumer) = SharedIterChain::new();
+        producer.include(0..3).unwrap();
+        producer.include(3..4).unwrap();
+
+        assert_eq!(Some(0), consumer.next());
+        producer.include_front(10..11).unwrap();
+        producer.include_front(20..21).unwrap();
+        drop(producer);
+
+        assert_eq!(vec![20, 10, 1, 2, 3], consumer.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn consumer_dropped() {
+        let (producer, consumer) = SharedIterChain::new();
+        producer.include(0..1).unwrap();
+        drop(consumer);
+
+        assert_eq!(Err(1..2), producer.include(1..2));
+        assert_eq!(Err(2..3), producer.include_front(2..3));
+    }
+
+    #[test]
+    fn polled_without_lock() {
+        type Boxed = Box<dyn Iterator<Item = usize> + Send>;
+
+        let (producer, consumer) = SharedIterChain::<Boxed>::new();
+        let inner = producer.clone();
+        let first: Boxed = Box::new(std::iter::once(0).inspect(move |_| {
+            // Deadlocks if the consumer holds the lock while polling.
+            assert!(inner.include(Box::new(5..6)).is_ok());
+        }));
+        assert!(producer.include(first).is_ok());
+        drop(producer);
+
+        assert_eq!(vec![0, 5], consumer.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn blocks_until_included() {
+        let (producer, consumer) = SharedIterChain::new();
+
+        let handle = thread::spawn(move || consumer.collect::<Vec<_>>());
+        for n in 0..3 {
+            producer.include(n * 2..n * 2 + 2).unwrap();
+        }
+        drop(producer);
+
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], handle.join().unwrap());
+    }
+
+    #[test]
+    fn many_producers() {
+        let (producer, consumer) = SharedIterChain::new();
+
+        let handles: Vec<_> = (0..4)
+            .map(|n| {
+                let producer = producer.clone();
+                thread::spawn(move || {
+                    for m in 0..10 {
+                        producer
+                            .include(n * 100 + m * 10..n * 100 + m * 10 + 10)
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(producer);
+
+        let mut items: Vec<_> = consumer.collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        items.sort_unstable();
+        assert_eq!((0..400).collect::<Vec<_>>(), items);
+    }
+}
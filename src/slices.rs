@@ -0,0 +1,167 @@
+//! Contiguous access to chains of slice iterators.
+
+use std::{slice, vec};
+
+use crate::IterChain;
+
+/// An iterator over the items of a contiguous slice, which can be viewed without advancing it.
+pub trait SliceIter: Iterator {
+    type Elem;
+
+    /// The items that have not been taken yet.
+    fn as_slice(&self) -> &[Self::Elem];
+}
+
+impl<'a, T> SliceIter for slice::Iter<'a, T> {
+    type Elem = T;
+
+    fn as_slice(&self) -> &[T] {
+        slice::Iter::as_slice(self)
+    }
+}
+
+impl<T> SliceIter for vec::IntoIter<T> {
+    type Elem = T;
+
+    fn as_slice(&self) -> &[T] {
+        vec::IntoIter::as_slice(self)
+    }
+}
+
+impl<I, M> IterChain<I, M>
+where
+    I: SliceIter,
+{
+    /// The items left in each pending iterator, from front to back. Iterators with no items left
+    /// are skipped.
+    ///
+    /// ```
+    /// let mut i = chaining_iter::IterChain::new();
+    /// i.include(vec![0, 1, 2].into_iter());
+    /// i.include(vec![3, 4].into_iter());
+    /// i.next();
+    ///
+    /// let slices: Vec<&[i32]> = i.as_slices().collect();
+    /// assert_eq!(vec![&[1, 2][..], &[3, 4][..]], slices);
+    /// ```
+    pub fn as_slices(&self) -> impl DoubleEndedIterator<Item = &[I::Elem]> {
+        self.segments()
+            .map(|segment| segment.iter.as_slice())
+            .filter(|slice| !slice.is_empty())
+    }
+
+    /// Take the first pending iterator with items left out of the chain.
+    fn take_front_slice_iter(&mut self) -> Option<I> {
+        loop {
+            let segment = self
+                .front
+                .take()
+                .or_else(|| self.iters.pop_front())
+                .or_else(|| self.back.take())?;
+            if !segment.iter.as_slice().is_empty() {
+                return Some(segment.iter);
+            }
+        }
+    }
+}
+
+impl<'a, T, M> IterChain<slice::Iter<'a, T>, M> {
+    /// The items left in the current iterator as one slice, advancing the chain past them.
+    ///
+    /// ```
+    /// let (a, b) = ([0, 1, 2], [3, 4]);
+    /// let mut i = chaining_iter::IterChain::new();
+    /// i.include(a.iter());
+    /// i.include(b.iter());
+    /// i.next();
+    ///
+    /// let mut out = Vec::new();
+    /// while let Some(chunk) = i.next_chunk_slice() {
+    ///     out.extend_from_slice(chunk);
+    /// }
+    /// assert_eq!(vec![1, 2, 3, 4], out);
+    /// ```
+    pub fn next_chunk_slice(&mut self) -> Option<&'a [T]> {
+        self.take_front_slice_iter().map(|iter| iter.as_slice())
+    }
+}
+
+impl<T, M> IterChain<vec::IntoIter<T>, M> {
+    /// The items left in the current iterator as one `Vec`, advancing the chain past them. The
+    /// items are moved rather than copied one by one, and the iterator's allocation is reused
+    /// where possible.
+    ///
+    /// The items are owned by the iterator, so unlike a chain of `slice::Iter`s they can't be
+    /// lent out as a slice once the chain has moved on. Use [`as_slices`](IterChain::as_slices)
+    /// to look at them in place.
+    pub fn next_chunk_vec(&mut self) -> Option<Vec<T>> {
+        self.take_front_slice_iter().map(Iterator::collect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut i: IterChain<slice::Iter<'_, i32>> = IterChain::new();
+        i.include([].iter());
+
+        assert_eq!(0, i.as_slices().count());
+        assert_eq!(None, i.next_chunk_slice());
+    }
+
+    #[test]
+    fn chunks_after_both_ends() {
+        let (a, b, c) = ([0, 1, 2], [3], [4, 5, 6]);
+        let mut i = IterChain::new();
+        i.include(a.iter());
+        i.include([].iter());
+        i.include(b.iter());
+        i.include(c.iter());
+
+        assert_eq!(Some(&0), i.next());
+        assert_eq!(Some(&6), i.next_back());
+        assert_eq!(Some(&[1, 2][..]), i.next_chunk_slice());
+        assert_eq!(Some(&[3][..]), i.next_chunk_slice());
+        assert_eq!(Some(&[4, 5][..]), i.next_chunk_slice());
+        assert_eq!(None, i.next_chunk_slice());
+        assert_eq!(None, i.next());
+    }
+
+    #[test]
+    fn chunks_then_items() {
+        let (a, b) = ([0, 1], [2, 3]);
+        let mut i = IterChain::new();
+        i.include(a.iter());
+        i.include(b.iter());
+
+        assert_eq!(Some(&[0, 1][..]), i.next_chunk_slice());
+        assert_eq!(vec![&2, &3], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn as_slices_from_back() {
+        let mut i = IterChain::new();
+        i.include(vec![0, 1].into_iter());
+        i.include(vec![2, 3].into_iter());
+        i.next_back();
+
+        let slices: Vec<&[i32]> = i.as_slices().rev().collect();
+        assert_eq!(vec![&[2][..], &[0, 1][..]], slices);
+    }
+
+    #[test]
+    fn owned_chunks() {
+        let mut i = IterChain::new();
+        i.include(vec![String::from("a"), String::from("b")].into_iter());
+        i.include(Vec::new().into_iter());
+        i.include(vec![String::from("c")].into_iter());
+        i.next();
+
+        assert_eq!(Some(vec![String::from("b")]), i.next_chunk_vec());
+        assert_eq!(Some(vec![String::from("c")]), i.next_chunk_vec());
+        assert_eq!(None, i.next_chunk_vec());
+    }
+}
@@ -0,0 +1,193 @@
+//! Pulling the iterators of a chain from an outer iterator.
+
+use std::iter::{self, Fuse};
+
+use crate::{sum_size_hints, IterChain, SegmentId};
+
+impl<I> IterChain<I>
+where
+    I: Iterator,
+{
+    /// Create a chain that takes its iterators from `outer`, one at a time, whenever it runs out.
+    /// Iterators included in the chain go before the ones still left in `outer`.
+    ///
+    /// ```
+    /// let mut i = chaining_iter::IterChain::from_source(vec![0..2, 2..4].into_iter());
+    ///
+    /// assert_eq!(Some(0), i.next());
+    /// i.include(10..12);
+    /// assert_eq!(vec![1, 10, 11, 2, 3], i.collect::<Vec<_>>());
+    /// ```
+    pub fn from_source<S>(outer: S) -> FromSource<S>
+    where
+        S: Iterator<Item = I>,
+    {
+        FromSource {
+            chain: IterChain::new(),
+            source: outer.fuse(),
+            back: None,
+        }
+    }
+}
+
+/// A chain that takes its iterators from an outer iterator as it needs them, like `Flatten`.
+///
+/// This `struct` is created by [`IterChain::from_source`].
+#[derive(Debug, Clone)]
+pub struct FromSource<S>
+where
+    S: Iterator,
+{
+    chain: IterChain<S::Item>,
+    source: Fuse<S>,
+    /// The iterator taken from the back of the source, if it is being polled from the back.
+    back: Option<S::Item>,
+}
+
+impl<S> FromSource<S>
+where
+    S: Iterator,
+    S::Item: Iterator,
+{
+    /// Include the given iterator after the ones already in the chain, and before the ones still
+    /// left in the source, returning its id.
+    pub fn include(&mut self, new_iter: S::Item) -> SegmentId {
+        self.chain.include(new_iter)
+    }
+
+    /// Include the given iterator at the front of the chain, returning its id.
+    pub fn include_front(&mut self, new_iter: S::Item) -> SegmentId {
+        self.chain.include_front(new_iter)
+    }
+
+    /// The iterators already taken from the source or included.
+    pub fn get_ref(&self) -> &IterChain<S::Item> {
+        &self.chain
+    }
+
+    /// The iterators already taken from the source or included.
+    pub fn get_mut(&mut self) -> &mut IterChain<S::Item> {
+        &mut self.chain
+    }
+}
+
+impl<S> Iterator for FromSource<S>
+where
+    S: Iterator,
+    S::Item: Iterator,
+{
+    type Item = <S::Item as Iterator>::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let val = self.chain.next();
+            if val.is_some() {
+                return val;
+            }
+            match self.source.next() {
+                Some(iter) => {
+                    self.chain.include(iter);
+                }
+                None => break,
+            }
+        }
+
+        let val = self.back.as_mut()?.next();
+        if val.is_none() {
+            self.back = None;
+        }
+        val
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = sum_size_hints(
+            iter::once(self.chain.size_hint()).chain(self.back.iter().map(Iterator::size_hint)),
+        );
+        match self.source.size_hint() {
+            (0, Some(0)) => (lower, upper),
+            _ => (lower, None),
+        }
+    }
+}
+
+impl<S> DoubleEndedIterator for FromSource<S>
+where
+    S: DoubleEndedIterator,
+    S::Item: DoubleEndedIterator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(back) = &mut self.back {
+                let val = back.next_back();
+                if val.is_some() {
+                    return val;
+                }
+                self.back = None;
+            }
+            match self.source.next_back() {
+                Some(iter) => self.back = Some(iter),
+                None => break,
+            }
+        }
+
+        self.chain.next_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::*;
+
+    #[test]
+    fn pulls_on_demand() {
+        let mut pulled = 0;
+        let outer = (0..3).map(|n| {
+            pulled += 1;
+            n * 10..n * 10 + 2
+        });
+        let mut i = IterChain::from_source(outer);
+
+        assert_eq!(Some(0), i.next());
+        assert_eq!(Some(1), i.next());
+        assert_eq!(Some(10), i.next());
+        drop(i);
+        assert_eq!(2, pulled);
+    }
+
+    #[test]
+    fn include_front_while_pulling() {
+        let mut i = IterChain::from_source(vec![0..2, 2..4].into_iter());
+
+        assert_eq!(Some(0), i.next());
+        i.include_front(10..11);
+        assert_eq!(vec![10, 1, 2, 3], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn double_ended() {
+        let mut i = IterChain::from_source(vec![0..2, 2..4, 4..6].into_iter());
+        i.include(10..11);
+
+        assert_eq!(Some(5), i.next_back());
+        assert_eq!(Some(10), i.next());
+        assert_eq!(Some(0), i.next());
+        assert_eq!(vec![1, 2, 3, 4], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn size_hint_once_source_empty() {
+        let mut i = IterChain::from_source(vec![0..2, 2..4].into_iter());
+        assert_eq!((0, None), i.size_hint());
+
+        i.next();
+        i.next();
+        i.next();
+        assert_eq!((1, Some(1)), i.size_hint());
+
+        let mut empty = IterChain::from_source(Vec::<Range<usize>>::new().into_iter());
+        assert_eq!((0, Some(0)), empty.size_hint());
+        assert_eq!(None, empty.next());
+    }
+}
@@ -0,0 +1,205 @@
+//! A chain of async streams, enabled by the `futures` feature.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+use futures::stream::{FusedStream, Stream, StreamExt};
+
+use crate::sum_size_hints;
+
+/// A chain of streams with type S, polled one after the other like an `IterChain`.
+///
+/// Including a stream wakes the task that last saw the chain pending, since the new stream may be
+/// ready. Streams must be `Unpin`, so ones that are not can be boxed with `StreamExt::boxed`. To
+/// include streams that only open once a future resolves, chain [`Deferred`](crate::Deferred)
+/// streams.
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::stream::{self, StreamExt};
+///
+/// let mut s = chaining_iter::StreamChain::new();
+/// s.include(stream::iter(3..5));
+/// s.include_front(stream::iter(0..3));
+///
+/// assert_eq!(vec![0, 1, 2, 3, 4], block_on(s.collect::<Vec<_>>()));
+/// ```
+#[derive(Debug)]
+pub struct StreamChain<S> {
+    streams: VecDeque<S>,
+    /// The waker of the last poll that returned `Poll::Pending`.
+    waker: Option<Waker>,
+}
+
+impl<S> StreamChain<S>
+where
+    S: Stream + Unpin,
+{
+    pub fn new() -> StreamChain<S> {
+        StreamChain {
+            streams: VecDeque::new(),
+            waker: None,
+        }
+    }
+
+    /// Include the given stream at the end of the chain.
+    pub fn include(&mut self, new_stream: S) {
+        self.streams.push_back(new_stream);
+        self.wake();
+    }
+
+    /// Include the given stream at the front of the chain.
+    pub fn include_front(&mut self, new_stream: S) {
+        self.streams.push_front(new_stream);
+        self.wake();
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<S> Default for StreamChain<S>
+where
+    S: Stream + Unpin,
+{
+    fn default() -> Self {
+        StreamChain::new()
+    }
+}
+
+impl<S> Stream for StreamChain<S>
+where
+    S: Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let stream = match self.streams.front_mut() {
+                Some(stream) => stream,
+                None => return Poll::Ready(None),
+            };
+            match stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(val)) => return Poll::Ready(Some(val)),
+                Poll::Ready(None) => {
+                    self.streams.pop_front();
+                }
+                Poll::Pending => {
+                    self.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        sum_size_hints(self.streams.iter().map(Stream::size_hint))
+    }
+}
+
+// `is_terminated` only says that polling now won't yield an item. Including another stream makes
+// the chain live again, which `FusedStream` allows.
+impl<S> FusedStream for StreamChain<S>
+where
+    S: Stream + Unpin,
+{
+    fn is_terminated(&self) -> bool {
+        self.streams.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::executor::block_on;
+    use futures::select_biased;
+    use futures::stream::{self, BoxStream};
+    use futures::task::waker;
+
+    use super::*;
+    use crate::test_util::CountWakes;
+
+    #[test]
+    fn empty() {
+        let mut s: StreamChain<stream::Iter<std::ops::Range<usize>>> = StreamChain::new();
+
+        assert_eq!(None, block_on(s.next()));
+    }
+
+    #[test]
+    fn skips_empty_streams() {
+        let mut s = StreamChain::new();
+        s.include(stream::iter(0..0));
+        s.include(stream::iter(0..2));
+        s.include(stream::iter(2..2));
+        s.include(stream::iter(2..3));
+
+        assert_eq!((3, Some(3)), s.size_hint());
+        assert_eq!(vec![0, 1, 2], block_on(s.collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn include_wakes_pending() {
+        let wakes = Arc::new(CountWakes::default());
+        let waker = waker(Arc::clone(&wakes));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut s: StreamChain<BoxStream<'static, usize>> = StreamChain::new();
+        s.include(stream::pending().boxed());
+        assert_eq!(Poll::Pending, s.poll_next_unpin(&mut cx));
+        assert_eq!(0, wakes.count());
+
+        s.include_front(stream::iter(0..1).boxed());
+        assert_eq!(1, wakes.count());
+        assert_eq!(Poll::Ready(Some(0)), s.poll_next_unpin(&mut cx));
+
+        // Only the last pending poll is woken, and only once.
+        s.include(stream::iter(1..2).boxed());
+        assert_eq!(1, wakes.count());
+    }
+
+    #[test]
+    fn include_after_exhausted() {
+        let mut s = StreamChain::new();
+        s.include(stream::iter(0..1));
+
+        assert_eq!(Some(0), block_on(s.next()));
+        assert_eq!(None, block_on(s.next()));
+        assert!(s.is_terminated());
+
+        s.include(stream::iter(1..2));
+        assert!(!s.is_terminated());
+        assert_eq!(Some(1), block_on(s.next()));
+    }
+
+    #[test]
+    fn select_skips_drained_chain() {
+        let mut s = StreamChain::new();
+        s.include(stream::iter(0..1));
+        let mut other = stream::iter(10..12).fuse();
+
+        block_on(async {
+            assert_eq!(Some(0), s.next().await);
+            assert_eq!(None, s.next().await);
+
+            // A biased select would take the drained chain's `None` first if it were polled.
+            let first = select_biased! {
+                val = s.next() => val,
+                val = other.next() => val,
+            };
+            assert_eq!(Some(10), first);
+
+            s.include(stream::iter(5..6));
+            let second = select_biased! {
+                val = s.next() => val,
+                val = other.next() => val,
+            };
+            assert_eq!(Some(5), second);
+        });
+    }
+}
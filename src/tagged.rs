@@ -0,0 +1,226 @@
+//! Tagging the items of an `IterChain` with the iterator they came from.
+
+use crate::{IterChain, SegmentId};
+
+impl<I, M> IterChain<I, M> {
+    /// Tag each item with the id of the iterator it came from, and its offset from the start of
+    /// that iterator.
+    ///
+    /// Items can only be taken from the back when I is also an `ExactSizeIterator`, since the
+    /// offset of an item taken from the back depends on how many items are left before it. For
+    /// other iterators, tags given as metadata work from both ends with
+    /// [`with_meta`](IterChain::with_meta).
+    ///
+    /// ```
+    /// let mut i = chaining_iter::IterChain::new();
+    /// let a = i.include(0..2);
+    /// let b = i.include(10..12);
+    ///
+    /// let mut i = i.tagged();
+    /// assert_eq!(Some((a, 0, 0)), i.next());
+    /// assert_eq!(Some((b, 1, 11)), i.next_back());
+    /// ```
+    pub fn tagged(self) -> Tagged<I, M>
+    where
+        I: Iterator,
+    {
+        Tagged { chain: self }
+    }
+
+    /// Pair each item with a copy of the metadata of the iterator it came from.
+    ///
+    /// ```
+    /// let mut i = chaining_iter::IterChain::new();
+    /// i.include_with_meta(0..2, "a");
+    /// i.include_with_meta(5..6, "b");
+    ///
+    /// let items: Vec<_> = i.with_meta().collect();
+    /// assert_eq!(vec![("a", 0), ("a", 1), ("b", 5)], items);
+    /// ```
+    pub fn with_meta(self) -> WithMeta<I, M>
+    where
+        I: Iterator,
+        M: Clone,
+    {
+        WithMeta { chain: self }
+    }
+}
+
+/// An iterator that yields the items of an `IterChain` together with where they came from.
+///
+/// This `struct` is created by [`IterChain::tagged`].
+#[derive(Debug, Clone)]
+pub struct Tagged<I, M = ()> {
+    chain: IterChain<I, M>,
+}
+
+impl<I, M> Tagged<I, M> {
+    pub fn get_ref(&self) -> &IterChain<I, M> {
+        &self.chain
+    }
+
+    /// Gives access to the underlying chain, so more iterators can be included.
+    pub fn get_mut(&mut self) -> &mut IterChain<I, M> {
+        &mut self.chain
+    }
+
+    pub fn into_inner(self) -> IterChain<I, M> {
+        self.chain
+    }
+}
+
+impl<I, M> Iterator for Tagged<I, M>
+where
+    I: Iterator,
+{
+    type Item = (SegmentId, usize, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let val = self.chain.next()?;
+        // The item came from the front iterator, unless the front is empty and the back iterator
+        // was polled in its place.
+        let segment = self.chain.front.as_ref().or(self.chain.back.as_ref())?;
+        Some((segment.id, segment.taken - 1, val))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chain.size_hint()
+    }
+}
+
+impl<I, M> DoubleEndedIterator for Tagged<I, M>
+where
+    I: DoubleEndedIterator + ExactSizeIterator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let val = self.chain.next_back()?;
+        let segment = self.chain.back.as_ref().or(self.chain.front.as_ref())?;
+        Some((segment.id, segment.taken + segment.iter.len(), val))
+    }
+}
+
+impl<I, M> ExactSizeIterator for Tagged<I, M> where I: ExactSizeIterator {}
+
+/// An iterator that yields the items of an `IterChain` together with the metadata of the
+/// iterator they came from.
+///
+/// This `struct` is created by [`IterChain::with_meta`].
+#[derive(Debug, Clone)]
+pub struct WithMeta<I, M> {
+    chain: IterChain<I, M>,
+}
+
+impl<I, M> WithMeta<I, M> {
+    pub fn get_ref(&self) -> &IterChain<I, M> {
+        &self.chain
+    }
+
+    /// Gives access to the underlying chain, so more iterators can be included.
+    pub fn get_mut(&mut self) -> &mut IterChain<I, M> {
+        &mut self.chain
+    }
+
+    pub fn into_inner(self) -> IterChain<I, M> {
+        self.chain
+    }
+}
+
+impl<I, M> Iterator for WithMeta<I, M>
+where
+    I: Iterator,
+    M: Clone,
+{
+    type Item = (M, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let val = self.chain.next()?;
+        let segment = self.chain.front.as_ref().or(self.chain.back.as_ref())?;
+        Some((segment.meta.clone(), val))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chain.size_hint()
+    }
+}
+
+impl<I, M> DoubleEndedIterator for WithMeta<I, M>
+where
+    I: DoubleEndedIterator,
+    M: Clone,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let val = self.chain.next_back()?;
+        let segment = self.chain.back.as_ref().or(self.chain.front.as_ref())?;
+        Some((segment.meta.clone(), val))
+    }
+}
+
+impl<I, M> ExactSizeIterator for WithMeta<I, M>
+where
+    I: ExactSizeIterator,
+    M: Clone,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_stable_across_includes() {
+        let mut i = IterChain::new();
+        let a = i.include(0..2);
+        let b = i.include_front(10..11);
+        let c = i.include(20..21);
+
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(
+            vec![(b, 0, 10), (a, 0, 0), (a, 1, 1), (c, 0, 20)],
+            i.tagged().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn offsets_from_both_ends() {
+        let mut i = IterChain::new();
+        let a = i.include(0..4);
+
+        let mut i = i.tagged();
+        assert_eq!(Some((a, 3, 3)), i.next_back());
+        assert_eq!(Some((a, 0, 0)), i.next());
+        assert_eq!(Some((a, 2, 2)), i.next_back());
+        assert_eq!(Some((a, 1, 1)), i.next());
+        assert_eq!(None, i.next());
+    }
+
+    #[test]
+    fn offsets_after_nth() {
+        let mut i = IterChain::new();
+        i.include(0..2);
+        let b = i.include(10..15);
+
+        assert_eq!(Some(11), i.nth(3));
+        let mut i = i.tagged();
+        assert_eq!(Some((b, 2, 12)), i.next());
+
+        let c = i.get_mut().include(20..21);
+        assert_eq!(Some((c, 0, 20)), i.next_back());
+    }
+
+    #[test]
+    fn meta_from_both_ends() {
+        let mut i = IterChain::new();
+        i.include_with_meta(0..2, 'a');
+        i.include_with_meta(2..4, 'b');
+        i.include_front_with_meta(4..5, 'c');
+
+        let mut i = i.with_meta();
+        assert_eq!(Some(('b', 3)), i.next_back());
+        assert_eq!(Some(('c', 4)), i.next());
+        assert_eq!(Some(('a', 0)), i.next());
+        assert_eq!(Some(('b', 2)), i.next_back());
+        assert_eq!(Some(('a', 1)), i.next_back());
+        assert_eq!(None, i.next());
+    }
+}
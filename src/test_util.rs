@@ -0,0 +1,22 @@
+//! Helpers shared by the tests of the async chains.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use futures::task::ArcWake;
+
+/// A waker that counts how many times it has been woken.
+#[derive(Debug, Default)]
+pub(crate) struct CountWakes(AtomicUsize);
+
+impl CountWakes {
+    pub(crate) fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl ArcWake for CountWakes {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
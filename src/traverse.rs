@@ -0,0 +1,216 @@
+//! Worklist traversal, where each item can include more iterators in the chain.
+
+use crate::IterChain;
+
+/// Decides whether a `Traverse` yields an item, or skips it as already visited.
+pub trait Visit<T> {
+    /// Returns `true` the first time the item is seen.
+    fn first_visit(&mut self, item: &T) -> bool;
+}
+
+/// Visit every item, even if it was seen before.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VisitAll;
+
+impl<T> Visit<T> for VisitAll {
+    fn first_visit(&mut self, _: &T) -> bool {
+        true
+    }
+}
+
+impl<T, V> Visit<T> for V
+where
+    V: FnMut(&T) -> bool,
+{
+    fn first_visit(&mut self, item: &T) -> bool {
+        self(item)
+    }
+}
+
+impl<I> IterChain<I>
+where
+    I: Iterator,
+{
+    /// Traverse a graph starting from `roots`. Each yielded item's children are included in the
+    /// chain, at the back for a breadth first traversal or at the front for a depth first one.
+    ///
+    /// The traversal is breadth first unless [`Traverse::depth_first`] is used.
+    ///
+    /// ```
+    /// use chaining_iter::IterChain;
+    ///
+    /// // Each number n has the children 2n + 1 and 2n + 2, up to 6.
+    /// let children = |&n: &u32| (2 * n + 1..=6).take(2);
+    ///
+    /// let bfs = IterChain::traverse((0..=0).take(1), children);
+    /// assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], bfs.collect::<Vec<_>>());
+    ///
+    /// let dfs = IterChain::traverse((0..=0).take(1), children).depth_first();
+    /// assert_eq!(vec![0, 1, 3, 4, 2, 5, 6], dfs.collect::<Vec<_>>());
+    /// ```
+    pub fn traverse<F>(roots: I, children: F) -> Traverse<I, F>
+    where
+        F: FnMut(&I::Item) -> I,
+    {
+        let mut chain = IterChain::new();
+        chain.include(roots);
+        Traverse {
+            chain,
+            children,
+            visited: VisitAll,
+            depth_first: false,
+        }
+    }
+}
+
+/// An iterator over a graph that includes the children of each item in its chain as it goes.
+///
+/// This `struct` is created by [`IterChain::traverse`].
+#[derive(Debug, Clone)]
+pub struct Traverse<I, F, V = VisitAll> {
+    chain: IterChain<I>,
+    children: F,
+    visited: V,
+    depth_first: bool,
+}
+
+impl<I, F, V> Traverse<I, F, V>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> I,
+    V: Visit<I::Item>,
+{
+    /// Include children at the front of the chain, so they are visited before the siblings of
+    /// their parent.
+    pub fn depth_first(mut self) -> Self {
+        self.depth_first = true;
+        self
+    }
+
+    /// Include children at the back of the chain, so they are visited after the siblings of
+    /// their parent.
+    pub fn breadth_first(mut self) -> Self {
+        self.depth_first = false;
+        self
+    }
+
+    /// Skip items that `visited` has already seen, without including their children. It should
+    /// return `true` the first time it sees an item.
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use chaining_iter::IterChain;
+    ///
+    /// // A cycle between 0, 1 and 2.
+    /// let edges = |&n: &u32| vec![(n + 1) % 3].into_iter();
+    ///
+    /// let mut seen = HashSet::new();
+    /// let nodes = IterChain::traverse(vec![0].into_iter(), edges)
+    ///     .skip_visited(move |n: &u32| seen.insert(*n));
+    /// assert_eq!(vec![0, 1, 2], nodes.collect::<Vec<_>>());
+    /// ```
+    pub fn skip_visited<W>(self, visited: W) -> Traverse<I, F, W>
+    where
+        W: Visit<I::Item>,
+    {
+        Traverse {
+            chain: self.chain,
+            children: self.children,
+            visited,
+            depth_first: self.depth_first,
+        }
+    }
+
+    /// Gives access to the underlying chain, so more iterators can be included.
+    pub fn get_mut(&mut self) -> &mut IterChain<I> {
+        &mut self.chain
+    }
+}
+
+impl<I, F, V> Iterator for Traverse<I, F, V>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> I,
+    V: Visit<I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let val = self.chain.next()?;
+            if !self.visited.first_visit(&val) {
+                continue;
+            }
+
+            let children = (self.children)(&val);
+            if self.depth_first {
+                self.chain.include_front(children);
+            } else {
+                self.chain.include(children);
+            }
+            return Some(val);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::vec::IntoIter;
+
+    use super::*;
+
+    fn tree(n: &u32) -> IntoIter<u32> {
+        match n {
+            0 => vec![1, 2],
+            1 => vec![3],
+            2 => vec![4, 5],
+            _ => vec![],
+        }
+        .into_iter()
+    }
+
+    #[test]
+    fn breadth_first() {
+        let i = IterChain::traverse(vec![0].into_iter(), tree);
+
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn depth_first() {
+        let i = IterChain::traverse(vec![0].into_iter(), tree).depth_first();
+
+        assert_eq!(vec![0, 1, 3, 2, 4, 5], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn skips_revisits() {
+        // 0 and 1 both point at 2, and 2 points back at 0.
+        let graph = |n: &u32| {
+            match n {
+                0 => vec![1, 2],
+                1 => vec![2],
+                2 => vec![0],
+                _ => vec![],
+            }
+            .into_iter()
+        };
+        let mut seen = HashSet::new();
+        let i = IterChain::traverse(vec![0].into_iter(), graph)
+            .depth_first()
+            .skip_visited(|n: &u32| seen.insert(*n));
+
+        assert_eq!(vec![0, 1, 2], i.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn include_more_roots() {
+        let mut i = IterChain::traverse(vec![1].into_iter(), tree);
+
+        assert_eq!(Some(1), i.next());
+        i.get_mut().include(vec![2].into_iter());
+        assert_eq!(vec![3, 2, 4, 5], i.collect::<Vec<_>>());
+    }
+}
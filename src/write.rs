@@ -0,0 +1,422 @@
+//! A chain of writers that rotates to the next one after a size limit.
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Write};
+
+/// A chain of writers with type W, written one after the other.
+///
+/// Data goes into the current writer until it reaches the byte or record limit, and then the chain
+/// rotates to the next writer. The full writer is flushed and handed to the rotation callback,
+/// or dropped if there is none. When no writers are left, one is made with the factory, if there
+/// is one. Dropping the chain drops the current writer as is, so call
+/// [`finish`](WriteChain::finish) to flush it and hand it to the rotation callback as well.
+///
+/// ```
+/// use std::io::Write;
+/// use std::sync::{Arc, Mutex};
+///
+/// let shards = Arc::new(Mutex::new(Vec::new()));
+/// let done = Arc::clone(&shards);
+///
+/// let mut w = chaining_iter::WriteChain::new()
+///     .with_max_bytes(4)
+///     .with_factory(|| Ok(Vec::new()))
+///     .on_rotate(move |shard: Vec<u8>| done.lock().unwrap().push(shard));
+///
+/// w.write_all(b"hello world").unwrap();
+/// assert_eq!(vec![b"hell".to_vec(), b"o wo".to_vec()], *shards.lock().unwrap());
+///
+/// w.finish().unwrap();
+/// assert_eq!(b"rld".to_vec(), shards.lock().unwrap()[2]);
+/// ```
+pub struct WriteChain<W> {
+    current: Option<W>,
+    queue: VecDeque<W>,
+    max_bytes: Option<u64>,
+    max_records: Option<u64>,
+    /// Bytes written to the current writer.
+    bytes: u64,
+    /// Records written to the current writer.
+    records: u64,
+    rotate: Option<Box<dyn FnMut(W) + Send>>,
+    factory: Option<Box<dyn FnMut() -> io::Result<W> + Send>>,
+}
+
+impl<W> WriteChain<W>
+where
+    W: Write,
+{
+    /// Create an empty chain without any limits.
+    pub fn new() -> WriteChain<W> {
+        WriteChain {
+            current: None,
+            queue: VecDeque::new(),
+            max_bytes: None,
+            max_records: None,
+            bytes: 0,
+            records: 0,
+            rotate: None,
+            factory: None,
+        }
+    }
+
+    /// Rotate to the next writer once this many bytes have been written to the current one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_bytes` is 0.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        assert!(max_bytes > 0, "max_bytes must be at least 1");
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Rotate to the next writer once this many records have been written to the current one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_records` is 0.
+    pub fn with_max_records(mut self, max_records: u64) -> Self {
+        assert!(max_records > 0, "max_records must be at least 1");
+        self.max_records = Some(max_records);
+        self
+    }
+
+    /// Make a new writer with the given function whenever the chain runs out of writers.
+    pub fn with_factory<F>(mut self, factory: F) -> Self
+    where
+        F: FnMut() -> io::Result<W> + Send + 'static,
+    {
+        self.factory = Some(Box::new(factory));
+        self
+    }
+
+    /// Hand each full writer to the given function after it is flushed.
+    pub fn on_rotate<F>(mut self, rotate: F) -> Self
+    where
+        F: FnMut(W) + Send + 'static,
+    {
+        self.rotate = Some(Box::new(rotate));
+        self
+    }
+
+    /// Include the given writer at the end of the chain.
+    pub fn include(&mut self, new_writer: W) {
+        self.queue.push_back(new_writer);
+    }
+
+    /// Include the given writer at the front of the chain, so it is used right after the current
+    /// one.
+    pub fn include_front(&mut self, new_writer: W) {
+        self.queue.push_front(new_writer);
+    }
+
+    /// The writer being written to, if any.
+    pub fn current_mut(&mut self) -> Option<&mut W> {
+        self.current.as_mut()
+    }
+
+    /// Take the writer being written to, without flushing it. Writers that have not been used
+    /// yet are dropped.
+    pub fn into_current(self) -> Option<W> {
+        self.current
+    }
+
+    /// Flush the writer being written to and hand it to the rotation callback, like a full one.
+    /// Writers that have not been used yet are dropped.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.retire()
+    }
+
+    /// Write a whole record to a single writer, and count it towards the record limit. If the
+    /// record doesn't fit in the bytes left in a writer that already has data, the chain rotates
+    /// first.
+    ///
+    /// ```
+    /// use std::io::Write;
+    ///
+    /// let mut w = chaining_iter::WriteChain::new().with_max_records(2);
+    /// w.include(Vec::new());
+    /// w.include(Vec::new());
+    ///
+    /// for record in [&b"a"[..], b"bc", b"d"].iter() {
+    ///     w.write_record(record).unwrap();
+    /// }
+    /// assert_eq!(Some(b"d".to_vec()), w.into_current());
+    /// ```
+    pub fn write_record(&mut self, record: &[u8]) -> io::Result<()> {
+        let fits = match self.max_bytes {
+            Some(max_bytes) => self.bytes + record.len() as u64 <= max_bytes,
+            None => true,
+        };
+        if !fits && self.bytes > 0 {
+            self.rotate()?;
+        }
+
+        let writer = self.writer()?;
+        writer.write_all(record)?;
+        self.bytes += record.len() as u64;
+        self.records += 1;
+        Ok(())
+    }
+
+    fn is_full(&self) -> bool {
+        self.max_bytes.is_some_and(|max| self.bytes >= max)
+            || self.max_records.is_some_and(|max| self.records >= max)
+    }
+
+    /// The writer to write to next, rotating first if the current one is full.
+    fn writer(&mut self) -> io::Result<&mut W> {
+        if self.current.is_none() || self.is_full() {
+            self.rotate()?;
+        }
+        match self.current.as_mut() {
+            Some(writer) => Ok(writer),
+            None => Err(no_writers()),
+        }
+    }
+
+    /// Flush the current writer, and hand it to the rotation callback.
+    fn retire(&mut self) -> io::Result<()> {
+        if let Some(mut full) = self.current.take() {
+            if let Err(err) = full.flush() {
+                self.current = Some(full);
+                return Err(err);
+            }
+            if let Some(rotate) = &mut self.rotate {
+                rotate(full);
+            }
+        }
+        Ok(())
+    }
+
+    /// Retire the current writer, and move on to the next one.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.retire()?;
+
+        self.bytes = 0;
+        self.records = 0;
+        self.current = match self.queue.pop_front() {
+            Some(writer) => Some(writer),
+            None => match &mut self.factory {
+                Some(factory) => Some(factory()?),
+                None => return Err(no_writers()),
+            },
+        };
+        Ok(())
+    }
+}
+
+fn no_writers() -> io::Error {
+    io::Error::new(io::ErrorKind::WriteZero, "no writers left in the chain")
+}
+
+impl<W> Default for WriteChain<W>
+where
+    W: Write,
+{
+    fn default() -> Self {
+        WriteChain::new()
+    }
+}
+
+impl<W> fmt::Debug for WriteChain<W>
+where
+    W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteChain")
+            .field("current", &self.current)
+            .field("queue", &self.queue)
+            .field("max_bytes", &self.max_bytes)
+            .field("max_records", &self.max_records)
+            .field("bytes", &self.bytes)
+            .field("records", &self.records)
+            .finish()
+    }
+}
+
+impl<W> Write for WriteChain<W>
+where
+    W: Write,
+{
+    /// Write to the current writer, up to its byte limit.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let max = match self.max_bytes {
+            Some(max_bytes) => {
+                let left = max_bytes.saturating_sub(self.bytes);
+                // A full writer is rotated by `writer`, which resets the count.
+                let left = if left == 0 { max_bytes } else { left };
+                buf.len().min(usize::try_from(left).unwrap_or(usize::MAX))
+            }
+            None => buf.len(),
+        };
+        let n = self.writer()?.write(&buf[..max])?;
+        self.bytes += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.current {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use super::*;
+
+    type Rotated = Arc<Mutex<Vec<Vec<u8>>>>;
+
+    fn collect_rotated(w: WriteChain<Vec<u8>>) -> (WriteChain<Vec<u8>>, Rotated) {
+        let rotated = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&rotated);
+        let w = w.on_rotate(move |full| sink.lock().unwrap().push(full));
+        (w, rotated)
+    }
+
+    fn rotated_shards(rotated: &Rotated) -> Vec<Vec<u8>> {
+        rotated.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn no_writers() {
+        let mut w: WriteChain<Vec<u8>> = WriteChain::new();
+
+        assert_eq!(
+            io::ErrorKind::WriteZero,
+            w.write_all(b"a").unwrap_err().kind()
+        );
+        assert_eq!(0, w.write(b"").unwrap());
+    }
+
+    #[test]
+    fn splits_bytes() {
+        let (mut w, rotated) = collect_rotated(WriteChain::new().with_max_bytes(3));
+        w.include(Vec::new());
+        w.include(Vec::new());
+        w.include(Vec::new());
+
+        w.write_all(b"abcdefg").unwrap();
+        assert_eq!(
+            vec![b"abc".to_vec(), b"def".to_vec()],
+            rotated_shards(&rotated)
+        );
+        assert_eq!(Some(b"g".to_vec()), w.into_current());
+    }
+
+    #[test]
+    fn runs_out_without_factory() {
+        let mut w = WriteChain::new().with_max_bytes(2);
+        w.include(Vec::new());
+
+        assert_eq!(2, w.write(b"abc").unwrap());
+        assert_eq!(io::ErrorKind::WriteZero, w.write(b"c").unwrap_err().kind());
+    }
+
+    #[test]
+    fn records_stay_whole() {
+        let (mut w, rotated) = collect_rotated(
+            WriteChain::new()
+                .with_max_bytes(4)
+                .with_factory(|| Ok(Vec::new())),
+        );
+
+        w.write_record(b"ab").unwrap();
+        w.write_record(b"cde").unwrap();
+        w.write_record(b"fghijk").unwrap();
+        w.write_record(b"l").unwrap();
+        assert_eq!(
+            vec![b"ab".to_vec(), b"cde".to_vec(), b"fghijk".to_vec()],
+            rotated_shards(&rotated)
+        );
+        assert_eq!(Some(b"l".to_vec()), w.into_current());
+    }
+
+    #[test]
+    fn include_front_goes_next() {
+        let (mut w, rotated) = collect_rotated(WriteChain::new().with_max_records(1));
+        w.include(vec![b'2']);
+        w.write_record(b"a").unwrap();
+        w.include_front(vec![b'1']);
+        w.include(vec![b'3']);
+        w.write_record(b"b").unwrap();
+        w.write_record(b"c").unwrap();
+
+        assert_eq!(
+            vec![b"2a".to_vec(), b"1b".to_vec()],
+            rotated_shards(&rotated)
+        );
+        assert_eq!(Some(b"3c".to_vec()), w.into_current());
+    }
+
+    #[test]
+    fn finish_rotates_current() {
+        let (mut w, rotated) = collect_rotated(WriteChain::new().with_max_bytes(2));
+        w.include(Vec::new());
+        w.include(Vec::new());
+        w.include(Vec::new());
+
+        w.write_all(b"abc").unwrap();
+        w.finish().unwrap();
+        assert_eq!(
+            vec![b"ab".to_vec(), b"c".to_vec()],
+            rotated_shards(&rotated)
+        );
+    }
+
+    #[test]
+    fn moves_to_worker_thread() {
+        let (mut w, rotated) = collect_rotated(
+            WriteChain::new()
+                .with_max_records(1)
+                .with_factory(|| Ok(Vec::new())),
+        );
+
+        thread::spawn(move || {
+            w.write_record(b"a").unwrap();
+            w.write_record(b"b").unwrap();
+            w.finish().unwrap();
+        })
+        .join()
+        .unwrap();
+        assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], rotated_shards(&rotated));
+    }
+
+    #[test]
+    fn temp_files() {
+        let dir = std::env::temp_dir().join(format!("chaining-iter-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut shard = 0;
+        let factory_dir = dir.clone();
+        let mut w = WriteChain::new().with_max_bytes(5).with_factory(move || {
+            shard += 1;
+            File::create(factory_dir.join(format!("shard-{}", shard)))
+        });
+        w.write_all(b"0123456789ab").unwrap();
+        w.flush().unwrap();
+        drop(w);
+
+        let shards: Vec<_> = (1..=3)
+            .map(|shard| fs::read(dir.join(format!("shard-{}", shard))).unwrap())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            vec![b"01234".to_vec(), b"56789".to_vec(), b"ab".to_vec()],
+            shards
+        );
+    }
+}